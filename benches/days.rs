@@ -0,0 +1,134 @@
+//! Parse and solve benchmarks for every day, on the real puzzle input and on
+//! scaled-up generated input, plus head-to-heads between the days that ship
+//! more than one implementation.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use advent_2021::*;
+use advent_2021::generate::{self, Rng};
+
+macro_rules! puzzle_input {
+    ($day:literal) => {
+        include_str!(concat!("../input/2021/day", $day, ".txt")).trim_end_matches('\n')
+    };
+}
+
+/// Benchmarks parsing and both parts of `S` separately, each under `name/size`.
+fn bench_solution<S: Solution>(c: &mut Criterion, name: &str, size: &str, input: &str) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+
+    group.bench_with_input(BenchmarkId::new("parse", size), input, |b, input| {
+        b.iter(|| S::parse(black_box(input)))
+    });
+
+    let parsed = S::parse(input).unwrap_or_else(|e| panic!("{} {}: {}", name, size, e));
+
+    group.bench_with_input(BenchmarkId::new("part1", size), &parsed, |b, parsed| {
+        b.iter(|| S::part1(black_box(parsed)))
+    });
+
+    group.bench_with_input(BenchmarkId::new("part2", size), &parsed, |b, parsed| {
+        b.iter(|| S::part2(black_box(parsed)))
+    });
+
+    group.finish();
+}
+
+fn puzzle_inputs(c: &mut Criterion) {
+    bench_solution::<day1::Puzzle>(c, "day1", "input", puzzle_input!(1));
+    bench_solution::<day2::Puzzle>(c, "day2", "input", puzzle_input!(2));
+    bench_solution::<day3::Puzzle>(c, "day3", "input", puzzle_input!(3));
+    bench_solution::<day4::Puzzle>(c, "day4", "input", puzzle_input!(4));
+    bench_solution::<day5::Puzzle>(c, "day5", "input", puzzle_input!(5));
+    bench_solution::<day6::Puzzle>(c, "day6", "input", puzzle_input!(6));
+    bench_solution::<day7::Puzzle>(c, "day7", "input", puzzle_input!(7));
+    bench_solution::<day8::Puzzle>(c, "day8", "input", puzzle_input!(8));
+    bench_solution::<day9::Puzzle>(c, "day9", "input", puzzle_input!(9));
+    bench_solution::<day10::Puzzle>(c, "day10", "input", puzzle_input!(10));
+    bench_solution::<day11::Puzzle>(c, "day11", "input", puzzle_input!(11));
+    // day 12 isn't solved yet, so there's nothing worth timing
+    bench_solution::<day13::Puzzle>(c, "day13", "input", puzzle_input!(13));
+    bench_solution::<day14::Puzzle>(c, "day14", "input", puzzle_input!(14));
+}
+
+fn scaled_inputs(c: &mut Criterion) {
+    let mut rng = Rng::new(2021);
+
+    bench_solution::<day1::Puzzle>(c, "day1", "x100", &generate::day1(&mut rng, 200_000));
+    bench_solution::<day2::Puzzle>(c, "day2", "x100", &generate::day2(&mut rng, 100_000));
+    bench_solution::<day3::Puzzle>(c, "day3", "x100", &generate::day3(&mut rng, 100_000, 12));
+    bench_solution::<day4::Puzzle>(c, "day4", "x10", &generate::day4(&mut rng, 1_000, 5));
+    bench_solution::<day5::Puzzle>(c, "day5", "x10", &generate::day5(&mut rng, 5_000, 1_000));
+    bench_solution::<day6::Puzzle>(c, "day6", "x100", &generate::day6(&mut rng, 30_000));
+    bench_solution::<day7::Puzzle>(c, "day7", "x10", &generate::day7(&mut rng, 10_000, 2_000));
+    bench_solution::<day8::Puzzle>(c, "day8", "x100", &generate::day8(&mut rng, 20_000));
+    bench_solution::<day9::Puzzle>(c, "day9", "x4", &generate::day9(&mut rng, 200, 200));
+    bench_solution::<day10::Puzzle>(c, "day10", "x100", &generate::day10(&mut rng, 9_000, 100));
+    bench_solution::<day13::Puzzle>(c, "day13", "x10", &generate::day13(&mut rng, 9_000, 12));
+    bench_solution::<day14::Puzzle>(c, "day14", "x50", &generate::day14(&mut rng, 1_000, 10));
+}
+
+fn day6_schools(c: &mut Criterion) {
+    let input = puzzle_input!(6);
+    let mut group = c.benchmark_group("day6/80 days");
+
+    group.bench_function("immutable", |b| b.iter(|| {
+        let mut school = black_box(input).parse::<day6::School>().unwrap();
+        (0..80).for_each(|_| school.step());
+        school.count()
+    }));
+
+    group.bench_function("mutable", |b| b.iter(|| {
+        let mut school = black_box(input).parse::<day6::InPlaceSchool>().unwrap();
+        (0..80).for_each(|_| school.step_mut());
+        school.count()
+    }));
+
+    group.bench_function("counting", |b| b.iter(|| {
+        black_box(input).parse::<day6::CountingSchool>().unwrap().count_after(80)
+    }));
+
+    group.finish();
+}
+
+fn day13_manuals(c: &mut Criterion) {
+    let manual = day13::Puzzle::parse(puzzle_input!(13)).unwrap();
+    let mut group = c.benchmark_group("day13/all folds");
+
+    group.bench_function("sparse", |b| b.iter(|| {
+        let mut manual = black_box(&manual).clone();
+        manual.do_all_folds();
+        manual.count_dots()
+    }));
+
+    group.bench_function("dense", |b| b.iter(|| {
+        let mut dense = day13::DenseManual::try_from(black_box(&manual)).unwrap();
+        dense.do_all_folds();
+        dense.count_dots()
+    }));
+
+    group.finish();
+}
+
+fn day14_polymers(c: &mut Criterion) {
+    let instrs = day14::Puzzle::parse(puzzle_input!(14)).unwrap();
+    let mut group = c.benchmark_group("day14/10 steps");
+
+    group.bench_function("naive", |b| b.iter(|| {
+        let mut polymer = black_box(&instrs).clone();
+        (0..10).for_each(|_| polymer.step());
+        polymer.occurrences()
+    }));
+
+    group.bench_function("pair count", |b| b.iter(|| {
+        let mut polymer = day14::PolymerInstrs2::new(black_box(&instrs));
+        (0..10).for_each(|_| polymer.step());
+        polymer.occurrences()
+    }));
+
+    group.finish();
+}
+
+criterion_group!(solutions, puzzle_inputs, scaled_inputs);
+criterion_group!(alternatives, day6_schools, day13_manuals, day14_polymers);
+criterion_main!(solutions, alternatives);
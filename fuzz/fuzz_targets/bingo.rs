@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day4, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        // the draws and every board, rather than one board at a time
+        if let Ok(game) = day4::Puzzle::parse(s) {
+            let _ = day4::Puzzle::part1(&game);
+            let _ = day4::Puzzle::part2(&game);
+        }
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day12, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(caves) = day12::Puzzle::parse(s) {
+            let _ = day12::Puzzle::part1(&caves);
+            let _ = day12::Puzzle::part2(&caves);
+        }
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day10, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(lines) = day10::Puzzle::parse(s) {
+            let _ = day10::Puzzle::part1(&lines);
+            let _ = day10::Puzzle::part2(&lines);
+        }
+    }
+});
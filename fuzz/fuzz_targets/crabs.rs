@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day7, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(crabs) = day7::Puzzle::parse(s) {
+            let _ = day7::Puzzle::part1(&crabs);
+            let _ = day7::Puzzle::part2(&crabs);
+        }
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day1, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    // the reader the binary streams input through, which takes any bytes
+    let _ = day1::sweep_reader(data);
+
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(depths) = day1::Puzzle::parse(s) {
+            let _ = day1::Puzzle::part1(&depths);
+            let _ = day1::Puzzle::part2(&depths);
+        }
+    }
+});
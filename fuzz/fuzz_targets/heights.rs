@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day9, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(heights) = day9::Puzzle::parse(s) {
+            let _ = day9::Puzzle::part1(&heights);
+            let _ = day9::Puzzle::part2(&heights);
+        }
+    }
+});
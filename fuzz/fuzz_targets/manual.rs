@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day13, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        // the dots and the folds, rather than one instruction at a time
+        if let Ok(manual) = day13::Puzzle::parse(s) {
+            let _ = day13::Puzzle::part1(&manual);
+            let _ = day13::Puzzle::part2(&manual);
+        }
+    }
+});
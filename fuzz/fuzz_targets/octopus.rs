@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day11, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(grid) = day11::Puzzle::parse(s) {
+            let _ = day11::Puzzle::part1(&grid);
+            let _ = day11::Puzzle::part2(&grid);
+        }
+    }
+});
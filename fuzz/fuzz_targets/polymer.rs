@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day14, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(instrs) = day14::Puzzle::parse(s) {
+            let _ = day14::Puzzle::part1(&instrs);
+            let _ = day14::Puzzle::part2(&instrs);
+        }
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day3, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(report) = day3::Puzzle::parse(s) {
+            let _ = day3::Puzzle::part1(&report);
+            let _ = day3::Puzzle::part2(&report);
+        }
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day6, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(school) = day6::Puzzle::parse(s) {
+            let _ = day6::Puzzle::part1(&school);
+            let _ = day6::Puzzle::part2(&school);
+        }
+    }
+});
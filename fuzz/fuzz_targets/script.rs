@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day2, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        // the whole script language, repeats and macros included
+        if let Ok(cmds) = day2::Puzzle::parse(s) {
+            let _ = day2::Puzzle::part1(&cmds);
+            let _ = day2::Puzzle::part2(&cmds);
+        }
+    }
+});
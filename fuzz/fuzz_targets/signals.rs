@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day8, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        // the whole generator, which splits each line on " | "
+        if let Ok(signals) = day8::Puzzle::parse(s) {
+            let _ = day8::Puzzle::part1(&signals);
+            let _ = day8::Puzzle::part2(&signals);
+        }
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use advent_2021::{day5, Solution};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(segments) = day5::Puzzle::parse(s) {
+            let _ = day5::Puzzle::part1(&segments);
+            let _ = day5::Puzzle::part2(&segments);
+        }
+    }
+});
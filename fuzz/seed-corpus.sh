@@ -0,0 +1,45 @@
+#!/bin/sh
+# Builds each fuzz target's seed corpus from the checked-in puzzle inputs,
+# one seed per thing the parser reads.
+set -eu
+
+cd "$(dirname "$0")"
+inputs=../input/2021
+
+# seeds <target> <file> [<pattern>]: one seed per line of <file> matching <pattern>
+seeds() {
+    mkdir -p "corpus/$1"
+    awk -v dir="corpus/$1" -v pattern="${3:-.}" '$0 ~ pattern { f = dir "/" NR; printf "%s", $0 > f; close(f) }' "$2"
+}
+
+seeds cmd "$inputs/day2.txt"
+seeds segment "$inputs/day5.txt"
+seeds instr "$inputs/day13.txt" "^fold"
+seeds signals "$inputs/day8.txt"
+
+# bingo boards are the blank-line separated sections after the called numbers
+mkdir -p corpus/bingo_board
+awk 'BEGIN { RS = "" } NR > 1 { f = "corpus/bingo_board/" NR; printf "%s", $0 > f; close(f) }' "$inputs/day4.txt"
+
+mkdir -p corpus/polymer
+cp "$inputs/day14.txt" corpus/polymer/day14
+
+# and each whole input too, for the targets that parse and solve them whole
+whole() {
+    mkdir -p "corpus/$1"
+    cp "$inputs/day$2.txt" "corpus/$1/day$2"
+}
+
+whole depths 1
+whole script 2
+whole report 3
+whole bingo 4
+whole vents 5
+whole school 6
+whole crabs 7
+whole signals 8
+whole heights 9
+whole chunks 10
+whole octopus 11
+whole caves 12
+whole manual 13
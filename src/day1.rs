@@ -1,44 +1,315 @@
-use aoc_runner_derive::{aoc, aoc_generator};
+//! Day 1: Sonar Sweep.
 
-#[aoc_generator(day1)]
-fn split_to_numbers(str: &str) -> Vec<u32> {
-    str.lines().map(|line| line.parse().unwrap()).collect()
+use std::collections::VecDeque;
+use std::io::BufRead;
+use aoc_runner_derive::aoc;
+use crate::registry::{self, Day, Run};
+use crate::Solution;
+
+/// How consecutive window sums of a sweep compare to one another.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SweepStats {
+    pub increases: usize,
+    pub decreases: usize,
+    pub unchanged: usize,
+    /// The first and last window of the longest stretch where every window sum
+    /// is bigger than the one before, by the index of the window's first depth.
+    /// The earliest wins a tie, and it's `None` if the sum never increases.
+    pub longest_increasing_run: Option<(usize, usize)>
 }
 
-#[aoc(day1, part1)]
-fn count_increasing(nums: &[u32]) -> u32 {
-    let mut last = None;
-    let mut count = 0;
-
-    for (_, &num) in nums.iter().enumerate() {
-        if let Some(last) = last {
-            if num > last {
-                count += 1;
+/// Compares the sum of each `window` consecutive depths with the sum of the
+/// window one step earlier as depths are pushed in. It only holds on to the
+/// current window, and keeps a running sum rather than re-adding it.
+#[derive(Debug, Clone)]
+pub struct WindowComparator {
+    window: usize,
+    buffer: VecDeque<u32>,
+    sum: u64,
+    start: usize,
+    run_start: usize,
+    stats: SweepStats
+}
+
+impl WindowComparator {
+    /// # Panics
+    ///
+    /// Panics if `window` is 0, as `slice::windows` does.
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "window size must be at least 1");
+
+        WindowComparator {
+            window,
+            buffer: VecDeque::with_capacity(window),
+            sum: 0,
+            start: 0,
+            run_start: 0,
+            stats: SweepStats::default()
+        }
+    }
+
+    pub fn push(&mut self, depth: u32) {
+        if self.buffer.len() < self.window {
+            self.buffer.push_back(depth);
+            self.sum += u64::from(depth);
+            return;
+        }
+
+        let last = self.sum;
+        let dropped = self.buffer.pop_front().unwrap();
+        self.buffer.push_back(depth);
+        self.sum = self.sum - u64::from(dropped) + u64::from(depth);
+        self.start += 1;
+
+        let stats = &mut self.stats;
+
+        if self.sum > last {
+            stats.increases += 1;
+
+            let longest = stats.longest_increasing_run.map_or(0, |(first, last)| last - first);
+            if self.start - self.run_start > longest {
+                stats.longest_increasing_run = Some((self.run_start, self.start));
             }
+        } else {
+            if self.sum < last {
+                stats.decreases += 1;
+            } else {
+                stats.unchanged += 1;
+            }
+
+            self.run_start = self.start;
+        }
+    }
+
+    pub fn stats(&self) -> SweepStats {
+        self.stats
+    }
+}
+
+/// Runs every depth through a [`WindowComparator`]. A window longer than the
+/// input is never compared, so every count is 0.
+///
+/// # Panics
+///
+/// Panics if `window` is 0.
+pub fn compare_windows<I: IntoIterator<Item=u32>>(depths: I, window: usize) -> SweepStats {
+    let mut comparator = WindowComparator::new(window);
+
+    for depth in depths {
+        comparator.push(depth);
+    }
+
+    comparator.stats()
+}
+
+/// Statistics for windows of 1 and 3, the two puzzle parts, from one pass.
+pub fn sweep<I: IntoIterator<Item=u32>>(depths: I) -> (SweepStats, SweepStats) {
+    let mut single = WindowComparator::new(1);
+    let mut triple = WindowComparator::new(3);
+
+    for depth in depths {
+        single.push(depth);
+        triple.push(depth);
+    }
+
+    (single.stats(), triple.stats())
+}
+
+/// Like `sweep`, reading one depth per line and skipping blank lines.
+pub fn sweep_reader<R: BufRead>(reader: R) -> Result<(SweepStats, SweepStats), String> {
+    let mut single = WindowComparator::new(1);
+    let mut triple = WindowComparator::new(3);
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("line {}: {}", i + 1, e))?;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
         }
 
-        last = Some(num);
+        let depth = line.parse::<u32>().map_err(|_| format!("line {}: not a depth: {:?}", i + 1, line))?;
+
+        single.push(depth);
+        triple.push(depth);
     }
 
-    count
+    Ok((single.stats(), triple.stats()))
+}
+
+/// Both parts from one pass over `reader`, so the runner never needs the whole
+/// sweep in memory.
+fn stream_parts(reader: &mut dyn BufRead) -> Result<Vec<Run>, String> {
+    registry::stream_with(reader, |reader| {
+        let (single, triple) = sweep_reader(reader)?;
+        Ok(vec![single.increases, triple.increases])
+    })
+}
+
+#[aoc(day1, part1)]
+fn count_increasing(input: &str) -> Result<usize, String> {
+    sweep_reader(input.as_bytes()).map(|(single, _)| single.increases)
 }
 
 #[aoc(day1, part2)]
-fn count_windows(nums: &[u32]) -> u32 {
-    let mut last: Option<u32> = None;
-    let mut count = 0;
+fn count_windows(input: &str) -> Result<usize, String> {
+    sweep_reader(input.as_bytes()).map(|(_, triple)| triple.increases)
+}
 
-    for (_, window) in nums.windows(3).enumerate() {
-        let sum = window.iter().sum();
+pub struct Puzzle;
 
-        if let Some(last) = last {
-            if sum > last {
-                count += 1;
+impl Solution for Puzzle {
+    type Input = Vec<u32>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        input.lines().enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| line.trim().parse().map_err(|_| format!("line {}: not a depth: {:?}", i + 1, line)))
+            .collect()
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        Ok(compare_windows(input.iter().copied(), 1).increases)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        Ok(compare_windows(input.iter().copied(), 3).increases)
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 1,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: Some(stream_parts)
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+199
+200
+208
+210
+200
+207
+240
+269
+260
+263
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(count_increasing(EXAMPLE).unwrap(), 7);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(count_windows(EXAMPLE).unwrap(), 5);
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(count_increasing(puzzle_input!(1)).unwrap(), 1233);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(count_windows(puzzle_input!(1)).unwrap(), 1275);
+    }
+
+    /// Reads `data`, then fails.
+    struct BrokenReader<'a> {
+        data: &'a [u8]
+    }
+
+    impl std::io::Read for BrokenReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.data.is_empty() {
+                return Err(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "sonar offline"));
             }
+
+            let n = std::cmp::min(buf.len(), self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+
+            Ok(n)
         }
+    }
+
+    #[test]
+    fn reads_depths_from_any_reader() {
+        let expected = sweep(example_depths());
+
+        assert_eq!(sweep_reader(EXAMPLE.as_bytes()), Ok(expected));
+        assert_eq!(sweep_reader(std::io::BufReader::with_capacity(4, EXAMPLE.as_bytes())), Ok(expected));
+        assert_eq!(sweep_reader(format!("\n{}\n  \n", EXAMPLE.replace('\n', " \n")).as_bytes()), Ok(expected));
+
+        let runs = stream_parts(&mut EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(runs.iter().map(|run| run.answer.clone()).collect::<Vec<_>>(), vec![7u32.into(), 5u32.into()]);
+    }
+
+    #[test]
+    fn reader_errors_have_line_numbers() {
+        assert_eq!(sweep_reader("199\n200\nabc\n".as_bytes()), Err("line 3: not a depth: \"abc\"".to_string()));
+        assert_eq!(sweep_reader("199\n-1\n".as_bytes()), Err("line 2: not a depth: \"-1\"".to_string()));
+        assert_eq!(
+            sweep_reader(&b"199\n200\n\xff\n"[..]),
+            Err("line 3: stream did not contain valid UTF-8".to_string())
+        );
+        assert_eq!(
+            sweep_reader(std::io::BufReader::new(BrokenReader { data: b"199\n200\n" })),
+            Err("line 3: sonar offline".to_string())
+        );
+    }
 
-        last = Some(sum);
+    fn example_depths() -> Vec<u32> {
+        EXAMPLE.lines().map(|l| l.parse().unwrap()).collect()
     }
 
-    count
+    #[test]
+    fn counts_every_kind_of_change() {
+        assert_eq!(compare_windows(example_depths(), 1), SweepStats {
+            increases: 7,
+            decreases: 2,
+            unchanged: 0,
+            longest_increasing_run: Some((0, 3))
+        });
+
+        // sums 607, 618, 618, 617, 647, 716, 769, 792
+        assert_eq!(compare_windows(example_depths(), 3), SweepStats {
+            increases: 5,
+            decreases: 1,
+            unchanged: 1,
+            longest_increasing_run: Some((3, 7))
+        });
+    }
+
+    #[test]
+    fn longest_run_is_the_earliest_of_the_longest() {
+        let stats = compare_windows([5, 1, 2, 3, 0, 4, 5, 6, 7, 1, 2, 3, 4], 1);
+
+        assert_eq!(stats.longest_increasing_run, Some((4, 8)));
+        assert_eq!(compare_windows([1, 2, 3, 1, 2, 3], 1).longest_increasing_run, Some((0, 2)));
+        assert_eq!(compare_windows([3, 3, 2, 1], 1).longest_increasing_run, None);
+    }
+
+    #[test]
+    fn windows_longer_than_the_input_compare_nothing() {
+        assert_eq!(compare_windows(example_depths(), 10), SweepStats::default());
+        assert_eq!(compare_windows(example_depths(), 11), SweepStats::default());
+        assert_eq!(compare_windows([], 3), SweepStats::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must be at least 1")]
+    fn empty_windows_panic() {
+        compare_windows(example_depths(), 0);
+    }
 }
@@ -1,9 +1,13 @@
+//! Day 10: Syntax Scoring.
+
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day};
+use crate::Solution;
 use itertools::Itertools;
 use Delimiter::*;
 
 #[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Eq)]
-enum Delimiter {
+pub enum Delimiter {
     OpenParen,
     OpenBracket,
     OpenCurly,
@@ -15,7 +19,7 @@ enum Delimiter {
 }
 
 impl Delimiter {
-    fn from_char(c: char) -> Option<Delimiter> {
+    pub fn from_char(c: char) -> Option<Delimiter> {
         match c {
             '(' => Some(OpenParen),
             '[' => Some(OpenBracket),
@@ -29,7 +33,7 @@ impl Delimiter {
         }
     }
 
-    fn matching(&self) -> Delimiter {
+    pub fn matching(&self) -> Delimiter {
         match self {
             OpenParen => CloseParen,
             OpenBracket => CloseBracket,
@@ -64,13 +68,17 @@ impl Delimiter {
 }
 
 #[aoc_generator(day10)]
-fn parse_input(input: &str) -> Vec<Vec<Delimiter>> {
-    input.lines().map(|line| {
-        line.chars().filter_map(|c| Delimiter::from_char(c)).collect()
+fn parse_input(input: &str) -> Result<Vec<Vec<Delimiter>>, String> {
+    input.lines().enumerate().map(|(i, line)| {
+        line.chars()
+            .map(|c| Delimiter::from_char(c).ok_or(format!("line {}: not a delimiter: {:?}", i + 1, c)))
+            .collect()
     }).collect()
 }
 
-fn corrupted_error_score(line: &[Delimiter]) -> Option<usize> {
+/// The score of the first closing delimiter that doesn't match what's open,
+/// or `None` if the line isn't corrupted.
+pub fn corrupted_error_score(line: &[Delimiter]) -> Option<usize> {
     let mut expected_delims = vec![];
 
     for &delim in line {
@@ -97,7 +105,9 @@ fn total_syntax_error_score(input: &[Vec<Delimiter>]) -> usize {
     input.iter().filter_map(|line| corrupted_error_score(line)).sum()
 }
 
-fn autocomplete_score(line: &[Delimiter]) -> Option<usize> {
+/// The closing delimiters an incomplete line is missing, in the order they're
+/// needed.
+pub fn completion(line: &[Delimiter]) -> Vec<Delimiter> {
     let mut expected_delims = vec![];
 
     for &delim in line {
@@ -111,22 +121,145 @@ fn autocomplete_score(line: &[Delimiter]) -> Option<usize> {
         }
     }
 
-    let mut score = 0;
-    for delim in expected_delims.iter().rev() {
-        score *= 5;
-        score += delim.autocomplete_score().unwrap();
+    expected_delims.reverse();
+    expected_delims
+}
+
+/// The score of the closing delimiters an incomplete line is missing, or `None`
+/// if there are too many of them to score.
+pub fn autocomplete_score(line: &[Delimiter]) -> Option<usize> {
+    let mut score = 0usize;
+    for delim in completion(line) {
+        score = score.checked_mul(5)?.checked_add(delim.autocomplete_score().unwrap())?;
     }
 
     Some(score)
 }
 
 #[aoc(day10, part2)]
-fn middle_autocomplete_score(input: &[Vec<Delimiter>]) -> usize {
-    let scores = input.iter()
-        .filter(|&line| corrupted_error_score(line).is_none())
-        .filter_map(|line| autocomplete_score(line))
+fn middle_autocomplete_score(input: &[Vec<Delimiter>]) -> Result<usize, String> {
+    let scores = input.iter().enumerate()
+        .filter(|(_, line)| corrupted_error_score(line).is_none())
+        .map(|(i, line)| autocomplete_score(line).ok_or(format!("line {}: autocomplete score overflows", i + 1)))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
         .sorted()
         .collect::<Vec<_>>();
 
-    *scores.get(scores.len() / 2).unwrap()
+    scores.get(scores.len() / 2).cloned().ok_or_else(|| "no lines are incomplete".to_string())
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = Vec<Vec<Delimiter>>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        Ok(total_syntax_error_score(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        middle_autocomplete_score(input)
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 10,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(total_syntax_error_score(&parse_input(EXAMPLE).unwrap()), 26397);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(middle_autocomplete_score(&parse_input(EXAMPLE).unwrap()), Ok(288957));
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(total_syntax_error_score(&parse_input(puzzle_input!(10)).unwrap()), 339411);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(middle_autocomplete_score(&parse_input(puzzle_input!(10)).unwrap()), Ok(2289754624));
+    }
+
+    #[test]
+    fn malformed_or_unscorable_lines_are_errors() {
+        assert_eq!(parse_input("[()]\n(x)"), Err("line 2: not a delimiter: 'x'".to_string()));
+        assert_eq!(middle_autocomplete_score(&parse_input("").unwrap()), Err("no lines are incomplete".to_string()));
+        assert_eq!(middle_autocomplete_score(&parse_input("(]").unwrap()), Err("no lines are incomplete".to_string()));
+
+        let deep = format!("()\n{}", "(".repeat(100));
+        assert_eq!(middle_autocomplete_score(&parse_input(&deep).unwrap()), Err("line 2: autocomplete score overflows".to_string()));
+    }
+
+    /// Lines that are incomplete but not corrupted: each step opens a random
+    /// delimiter or closes the innermost open one, if any.
+    fn incomplete_line() -> impl Strategy<Value = Vec<Delimiter>> {
+        const OPENERS: [Delimiter; 4] = [OpenParen, OpenBracket, OpenCurly, OpenAngle];
+
+        prop::collection::vec(prop::option::of(prop::sample::select(&OPENERS[..])), 0..40)
+            .prop_map(|steps| {
+                let mut line = vec![];
+                let mut open = vec![];
+
+                for step in steps {
+                    match step {
+                        Some(opener) => {
+                            line.push(opener);
+                            open.push(opener.matching());
+                        },
+                        None => line.extend(open.pop())
+                    }
+                }
+
+                line
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn autocomplete_makes_line_valid(line in incomplete_line()) {
+            prop_assert_eq!(corrupted_error_score(&line), None);
+
+            let mut completed = line.clone();
+            completed.extend(completion(&line));
+
+            prop_assert_eq!(corrupted_error_score(&completed), None);
+            prop_assert!(completion(&completed).is_empty());
+            prop_assert_eq!(autocomplete_score(&completed), Some(0));
+        }
+    }
 }
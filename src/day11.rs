@@ -1,142 +1,206 @@
+//! Day 11: Dumbo Octopus.
+
+use std::collections::HashSet;
 use std::str::FromStr;
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day};
+use crate::Solution;
 
-#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
-enum OctoState {
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum OctoState {
     EnergyLevel(usize),
     Flash
 }
 
+/// Which cells count as adjacent when an octopus flashes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Neighborhood {
+    /// The 4 orthogonal neighbours.
+    VonNeumann,
+    /// The 8 orthogonal and diagonal neighbours.
+    Moore
+}
+
+/// Rules for one run of the octopus automaton. The default is the puzzle's: an
+/// octopus flashes once its energy exceeds 9, resets to 0, and energises its 8
+/// neighbours on a grid with hard edges.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct OctoRules {
+    pub flash_threshold: usize,
+    pub reset_value: usize,
+    pub neighborhood: Neighborhood,
+    pub wrap: bool
+}
+
+impl Default for OctoRules {
+    fn default() -> Self {
+        OctoRules {
+            flash_threshold: 9,
+            reset_value: 0,
+            neighborhood: Neighborhood::Moore,
+            wrap: false
+        }
+    }
+}
+
+impl OctoRules {
+    fn offsets(&self) -> &'static [(i64, i64)] {
+        match self.neighborhood {
+            Neighborhood::VonNeumann => &[
+                          (0, -1),
+                (-1, 0),           (1, 0),
+                          (0, 1),
+            ],
+            Neighborhood::Moore => &[
+                (-1, -1), (0, -1), (1, -1),
+                (-1, 0),  /*(0, 0),*/ (1, 0),
+                (-1, 1),  (0, 1),  (1, 1),
+            ]
+        }
+    }
+
+    fn adjacent_coordinates(&self, width: usize, height: usize, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let (w, h) = (width as i64, height as i64);
+
+        let mut coords = self.offsets().iter()
+            .map(|&(dx, dy)| (x as i64 + dx, y as i64 + dy))
+            .filter_map(|(x, y)| {
+                if self.wrap {
+                    Some((x.rem_euclid(w), y.rem_euclid(h)))
+                } else if 0 <= x && x < w && 0 <= y && y < h {
+                    Some((x, y))
+                } else {
+                    None
+                }
+            })
+            .map(|(x, y)| (x as usize, y as usize))
+            .collect::<Vec<_>>();
+
+        // on a torus narrower than 3 cells, several offsets land on the same cell
+        coords.sort_unstable();
+        coords.dedup();
+
+        coords
+    }
+}
+
 #[derive(Debug, Clone)]
-struct OctoGrid {
+pub struct OctoGrid {
     grid: Vec<Vec<OctoState>>,
-    flash_count: usize,
-    step_count: usize
+    rules: OctoRules,
+    pub flash_count: usize,
+    pub step_count: usize
 }
 
 impl OctoGrid {
-    fn new(grid: Vec<Vec<OctoState>>) -> Self {
-        OctoGrid { grid, flash_count: 0, step_count: 0 }
-    }
+    pub fn new(grid: Vec<Vec<OctoState>>) -> Result<Self, &'static str> {
+        let width = grid.first().map_or(0, |row| row.len());
 
-    fn size(&self) -> usize {
-        let height = self.grid.len();
-        let width = self.grid.first().unwrap().len();
+        if width == 0 {
+            return Err("grid is empty");
+        }
 
-        height * width
+        if grid.iter().any(|row| row.len() != width) {
+            return Err("grid isn't rectangular");
+        }
+
+        Ok(OctoGrid { grid, rules: OctoRules::default(), flash_count: 0, step_count: 0 })
     }
 
-    fn adjacent_coordinates(width: usize, height: usize, x: usize, y: usize) -> Vec<(usize, usize)> {
-        let x = x as i32;
-        let y = y as i32;
+    pub fn with_rules(self, rules: OctoRules) -> Self {
+        OctoGrid { rules, ..self }
+    }
 
-        let coords = vec![
-            (x-1, y-1), (x, y-1), (x+1, y-1),
-            (x-1, y),  /*(x, y),*/ (x+1, y),
-            (x-1, y+1), (x, y+1), (x+1, y+1),
-        ];
+    pub fn size(&self) -> usize {
+        let height = self.grid.len();
+        let width = self.grid.first().unwrap().len();
 
-        coords.iter()
-            .filter(|&&(x,y)| 0 <= x && x < (width as i32) && 0 <= y && y < (height as i32))
-            .map(|&(x, y)| (x as usize, y as usize))
-            .collect()
+        height * width
     }
 
-    fn maybe_flash(grid: &mut Vec<Vec<OctoState>>, x: usize, y: usize) {
-        let height = grid.len();
-        let width = grid.first().unwrap().len();
-        let val = grid[y][x];
-
-        let adjacent_coordinates = OctoGrid::adjacent_coordinates(width, height, x, y);
+    fn maybe_flash(&mut self, x: usize, y: usize) {
+        let height = self.grid.len();
+        let width = self.grid.first().unwrap().len();
+        let threshold = self.rules.flash_threshold;
 
-        let mut flashed_coords = vec![];
+        let mut pending = vec![(x, y)];
 
-        match val {
-            OctoState::EnergyLevel(e) if e > 9 => {
-                // mark as flashed
-                grid[y][x] = OctoState::Flash;
+        while let Some((x, y)) = pending.pop() {
+            match self.grid[y][x] {
+                OctoState::EnergyLevel(e) if e > threshold => {
+                    // mark as flashed
+                    self.grid[y][x] = OctoState::Flash;
 
-                // increase surrounding energy levels by 1
-                for &(x1, y1) in &adjacent_coordinates {
-                    let v = grid[y1][x1];
-                    let value = match v {
-                        OctoState::EnergyLevel(e) => {
-                            if e >= 9 {
-                                flashed_coords.push((x1, y1));
+                    // increase surrounding energy levels by 1
+                    for (x1, y1) in self.rules.adjacent_coordinates(width, height, x, y) {
+                        if let OctoState::EnergyLevel(e) = self.grid[y1][x1] {
+                            if e >= threshold {
+                                pending.push((x1, y1));
                             }
 
-                            OctoState::EnergyLevel(e + 1)
-                        },
-                        _ => v
-                    };
-
-                    grid[y1][x1] = value;
+                            self.grid[y1][x1] = OctoState::EnergyLevel(e + 1);
+                        }
+                    }
                 }
+                _ => {}
             }
-            _ => {}
-        }
-
-        for (x, y) in flashed_coords {
-            OctoGrid::maybe_flash(grid, x, y);
         }
     }
 
-    fn step(&mut self) {
+    pub fn step(&mut self) {
         let height = self.grid.len();
         let width = self.grid.first().unwrap().len();
-        let mut new_grid = self.grid.clone();
 
-        for y in 0..height {
-            for x in 0..width {
-                let val = new_grid[y][x];
-                new_grid[y][x] = match val {
-                    OctoState::EnergyLevel(e) => OctoState::EnergyLevel(e+1),
-                    _ => val
-                }
+        for val in self.grid.iter_mut().flatten() {
+            if let OctoState::EnergyLevel(e) = *val {
+                *val = OctoState::EnergyLevel(e + 1);
             }
         }
 
         for y in 0..height {
             for x in 0..width {
-                OctoGrid::maybe_flash(&mut new_grid, x, y)
+                self.maybe_flash(x, y)
             }
         }
 
         let mut flash_count = 0;
-        for y in 0..height {
-            for x in 0..width {
-                let val = new_grid[y][x];
-                new_grid[y][x] = match val {
-                    OctoState::Flash => {
-                        flash_count += 1;
-                        OctoState::EnergyLevel(0)
-                    },
-                    _ => val
-                }
+        for val in self.grid.iter_mut().flatten() {
+            if *val == OctoState::Flash {
+                flash_count += 1;
+                *val = OctoState::EnergyLevel(self.rules.reset_value);
             }
         }
 
         self.flash_count += flash_count;
         self.step_count += 1;
-        self.grid = new_grid;
     }
 }
 
 impl FromStr for OctoGrid {
-    type Err = &'static str;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(OctoGrid::new(s.lines().map(|line| {
-            line.chars().filter_map(|c| {
-                c.to_digit(10).map(|d| OctoState::EnergyLevel(d as usize))
+        let width = s.lines().next().map_or(0, |l| l.len());
+
+        let grid = s.lines().enumerate().map(|(i, line)| {
+            if line.len() != width {
+                return Err(format!("line {} has {} octopuses but line 1 has {}", i + 1, line.len(), width));
+            }
+
+            line.chars().map(|c| {
+                c.to_digit(10)
+                    .map(|d| OctoState::EnergyLevel(d as usize))
+                    .ok_or(format!("line {}: not an energy level: {:?}", i + 1, c))
             }).collect()
-        }).collect()))
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        OctoGrid::new(grid).map_err(|e| e.to_string())
     }
 }
 
 #[aoc_generator(day11)]
-fn parse_input(input: &str) -> OctoGrid {
-    input.parse().unwrap()
+fn parse_input(input: &str) -> Result<OctoGrid, String> {
+    input.parse()
 }
 
 #[aoc(day11, part1)]
@@ -150,19 +214,132 @@ fn total_flashes(input: &OctoGrid) -> usize {
     grid.flash_count
 }
 
+/// The step is deterministic, so once the grid comes back to a state it's been
+/// in before it's stuck in a cycle that, so far, hasn't synced, and never will.
 #[aoc(day11, part2)]
-fn first_synced_flash_step(input: &OctoGrid) -> usize {
+fn first_synced_flash_step(input: &OctoGrid) -> Result<usize, String> {
     let mut grid = input.clone();
+    let mut seen = HashSet::new();
 
-    loop {
+    while seen.insert(grid.grid.clone()) {
         let old_flash_count = grid.flash_count;
 
         grid.step();
 
         if grid.flash_count - old_flash_count == grid.size() {
-            break;
+            return Ok(grid.step_count);
         }
     }
 
-    grid.step_count
-}
\ No newline at end of file
+    Err(format!("the octopuses never all flash at once; the grid repeats after step {}", grid.step_count))
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = OctoGrid;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        Ok(total_flashes(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        first_synced_flash_step(input)
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 11,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(total_flashes(&parse_input(EXAMPLE).unwrap()), 1656);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(first_synced_flash_step(&parse_input(EXAMPLE).unwrap()), Ok(195));
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(total_flashes(&parse_input(puzzle_input!(11)).unwrap()), 1673);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(first_synced_flash_step(&parse_input(puzzle_input!(11)).unwrap()), Ok(279));
+    }
+
+    fn stepped(grid: &str, rules: OctoRules) -> Vec<Vec<OctoState>> {
+        let mut grid = parse_input(grid).unwrap().with_rules(rules);
+        grid.step();
+        grid.grid
+    }
+
+    #[test]
+    fn von_neumann_neighbours_skip_diagonals() {
+        let grid = "000\n090\n000";
+
+        assert_eq!(stepped(grid, OctoRules::default()), parse_input("222\n202\n222").unwrap().grid);
+        assert_eq!(
+            stepped(grid, OctoRules { neighborhood: Neighborhood::VonNeumann, ..OctoRules::default() }),
+            parse_input("121\n202\n121").unwrap().grid
+        );
+    }
+
+    #[test]
+    fn wrapping_grids_reach_across_edges() {
+        let grid = "900\n000\n000";
+
+        assert_eq!(stepped(grid, OctoRules::default()), parse_input("021\n221\n111").unwrap().grid);
+        assert_eq!(stepped(grid, OctoRules { wrap: true, ..OctoRules::default() }), parse_input("022\n222\n222").unwrap().grid);
+    }
+
+    #[test]
+    fn flashed_octopuses_reset_to_the_reset_value() {
+        assert_eq!(stepped("09\n00", OctoRules { reset_value: 5, ..OctoRules::default() }), parse_input("25\n22").unwrap().grid);
+    }
+
+    #[test]
+    fn grids_that_never_sync_are_errors() {
+        // the two octopuses take turns flashing, and are back where they
+        // started after 10 steps
+        assert!(first_synced_flash_step(&parse_input("05").unwrap()).is_err());
+    }
+
+    #[test]
+    fn malformed_grids_are_errors() {
+        assert_eq!(parse_input("12\n345").err(), Some("line 2 has 3 octopuses but line 1 has 2".to_string()));
+        assert_eq!(parse_input("12\n3a").err(), Some("line 2: not an energy level: 'a'".to_string()));
+        assert_eq!(parse_input("").err(), Some("grid is empty".to_string()));
+    }
+}
@@ -1,11 +1,20 @@
-use aoc_runner_derive::{aoc};
+//! Day 12: Passage Pathing. Not solved yet, so it's left out of the registry.
 
-#[aoc(day12, part1)]
-fn count_paths(input: &str) -> usize {
-    let _edges = input.lines().map(|l| {
-        l.split_once("-").unwrap()
-    }).collect::<Vec<(&str, &str)>>();
+use aoc_runner_derive::{aoc, aoc_generator};
+use crate::Solution;
+
+#[aoc_generator(day12)]
+fn parse_input(input: &str) -> Result<Vec<(String, String)>, String> {
+    input.lines().enumerate().map(|(i, l)| {
+        l.split_once('-')
+            .filter(|(a, b)| !a.is_empty() && !b.is_empty())
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .ok_or(format!("line {}: not a passage: {:?}", i + 1, l))
+    }).collect()
+}
 
+#[aoc(day12, part1)]
+fn count_paths(_edges: &[(String, String)]) -> Result<usize, String> {
     // let mut graph = DiGraphMap::default();
     //
     // for (a, b) in edges {
@@ -17,5 +26,52 @@ fn count_paths(input: &str) -> usize {
     //
     // println!("res: {:?}", res);
 
-    0
-}
\ No newline at end of file
+    Err("day 12 part 1 isn't solved yet".to_string())
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = Vec<(String, String)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        count_paths(input)
+    }
+
+    fn part2(_input: &Self::Input) -> Result<Self::Part2, String> {
+        Err("day 12 part 2 isn't solved yet".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end
+";
+
+    #[test]
+    #[ignore = "day 12 isn't solved yet"]
+    fn part1_example() {
+        assert_eq!(count_paths(&parse_input(EXAMPLE).unwrap()), Ok(10));
+    }
+
+    #[test]
+    fn malformed_passages_report_line_numbers() {
+        assert_eq!(parse_input("start-A\nA end"), Err("line 2: not a passage: \"A end\"".to_string()));
+        assert_eq!(parse_input("start-"), Err("line 1: not a passage: \"start-\"".to_string()));
+    }
+}
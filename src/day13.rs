@@ -1,119 +1,275 @@
+//! Day 13: Transparent Origami.
+
+use std::collections::HashSet;
 use std::str::FromStr;
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day, Run, Variant};
+use crate::{Answer, Solution};
+use yap::{IntoTokens, Tokens};
+use crate::ocr;
 
-#[derive(Debug, Copy, Clone)]
-enum Instr {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Instr {
     FoldX(usize),
     FoldY(usize)
 }
 
+fn skip_whitespace(t: &mut impl Tokens<Item=char>) -> usize {
+    t.skip_tokens_while(|c| c.is_whitespace())
+}
+
+/// Like `Tokens::tokens`, but doesn't accept a truncated `word` at the end of input.
+fn keyword(t: &mut impl Tokens<Item=char>, word: &str) -> bool {
+    let start = t.offset();
+    t.tokens(word.chars()) && t.offset() - start == word.chars().count()
+}
+
+fn parse_instr(t: &mut impl Tokens<Item=char>) -> Result<Instr, &'static str> {
+    skip_whitespace(t);
+    if !keyword(t, "fold") {
+        return Err("expected 'fold'");
+    }
+    if skip_whitespace(t) == 0 || !keyword(t, "along") {
+        return Err("expected 'along'");
+    }
+    if skip_whitespace(t) == 0 {
+        return Err("expected an axis");
+    }
+    let axis = t.one_of_tokens("xy".chars()).ok_or("expected axis 'x' or 'y'")?;
+
+    skip_whitespace(t);
+    if !t.token('=') {
+        return Err("expected '='");
+    }
+
+    skip_whitespace(t);
+    let digits = t.tokens_while(|c| c.is_ascii_digit()).collect::<String>();
+    if digits.is_empty() {
+        return Err("expected a fold position");
+    }
+    let line = digits.parse::<usize>().ok().filter(|&line| line <= MAX_COORDINATE).ok_or("fold position too large")?;
+
+    skip_whitespace(t);
+    if t.peek().is_some() {
+        return Err("unexpected characters after fold position");
+    }
+
+    Ok(match axis {
+        'x' => Instr::FoldX(line),
+        _ => Instr::FoldY(line)
+    })
+}
+
 impl FromStr for Instr {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &s[11..12] {
-            "x" => Ok(Instr::FoldX(*&s[13..].parse::<usize>().unwrap())),
-            "y" => Ok(Instr::FoldY(*&s[13..].parse::<usize>().unwrap())),
-            _ => Err("couldn't parse")
-        }
+        parse_instr(&mut s.into_tokens())
     }
 }
 
+pub type Dot = (usize, usize);
+
+/// The furthest a dot or fold can be from the edge of the sheet, which keeps
+/// folding from overflowing.
+pub const MAX_COORDINATE: usize = u32::MAX as usize;
+
+/// Maps coordinate `c` on a sheet `size` long onto the sheet left after folding
+/// along `line`. Whichever half is longer sets the new size; the shorter half is
+/// aligned against the fold, so folds needn't sit in the middle. Dots on the
+/// fold line itself disappear.
+fn reflect(c: usize, line: usize, size: usize) -> Option<usize> {
+    let new_size = folded_size(line, size);
+    let offset = new_size - line;
+
+    match c.cmp(&line) {
+        std::cmp::Ordering::Less => Some(c + offset),
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Greater => Some(2 * line + offset - c)
+    }
+}
+
+fn folded_size(line: usize, size: usize) -> usize {
+    std::cmp::max(line, size.saturating_sub(line + 1))
+}
+
 #[derive(Debug, Clone)]
-struct Manual {
-    grid: Vec<Vec<bool>>,
+pub struct Manual {
+    dots: HashSet<Dot>,
+    width: usize,
+    height: usize,
     instructions: Vec<Instr>
 }
 
 #[aoc_generator(day13)]
-fn parse_input(input: &str) -> Manual {
-    let (coords, instrs) = input.split_once("\n\n").unwrap();
+fn parse_input(input: &str) -> Result<Manual, String> {
+    let (coords, instrs) = input.split_once("\n\n").ok_or("missing blank line before fold instructions")?;
 
-    let coords = coords.lines().map(|l| {
-        let (x, y) = l.split_once(",").unwrap();
-        (x.parse::<usize>().unwrap(), y.parse::<usize>().unwrap())
-    }).collect::<Vec<_>>();
+    let dots = coords.lines().enumerate().map(|(i, l)| {
+        l.split_once(",")
+            .and_then(|(x, y)| Some((x.trim().parse::<usize>().ok()?, y.trim().parse::<usize>().ok()?)))
+            .ok_or(format!("line {}: couldn't parse dot: {:?}", i + 1, l))
+            .and_then(|(x, y)| {
+                if x.max(y) > MAX_COORDINATE {
+                    return Err(format!("line {}: dot is too far out: {:?}", i + 1, l));
+                }
+
+                Ok((x, y))
+            })
+    }).collect::<Result<HashSet<_>, _>>()?;
 
-    let max_x = coords.iter().map(|&(x, _)| x).max().unwrap();
-    let max_y = coords.iter().map(|&(_, y)| y).max().unwrap();
+    let width = dots.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+    let height = dots.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
 
-    let mut grid = vec![vec![false; max_x+1]; max_y+1];
+    // instruction lines follow the dots and the blank separator line
+    let first_instr_line = coords.lines().count() + 2;
 
-    for (x, y) in coords {
-        grid[y][x] = true;
+    let (instructions, errors): (Vec<_>, Vec<_>) = instrs.lines().enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            l.parse::<Instr>()
+                .map_err(|e| format!("line {}: {}: {:?}", first_instr_line + i, e, l))
+        })
+        .partition(|r| r.is_ok());
+
+    if !errors.is_empty() {
+        return Err(errors.into_iter().filter_map(Result::err).collect::<Vec<_>>().join("\n"));
     }
 
-    let instructions = instrs.lines().filter_map(|l| {
-        l.parse::<Instr>().ok()
-    }).collect::<Vec<_>>();
+    let instructions = instructions.into_iter().filter_map(Result::ok).collect();
 
-    Manual { grid, instructions }
+    Ok(Manual { dots, width, height, instructions })
 }
 
 impl Manual {
-    fn do_fold(&mut self) {
+    pub fn dots(&self) -> &HashSet<Dot> {
+        &self.dots
+    }
+
+    /// Applies the next fold instruction, if there is one.
+    pub fn do_fold(&mut self) {
+        if self.instructions.is_empty() {
+            return;
+        }
+
         let instr = self.instructions.remove(0);
 
+        let (width, height) = (self.width, self.height);
+
+        self.dots = match instr {
+            Instr::FoldY(line) => {
+                self.height = folded_size(line, height);
+                self.dots.iter()
+                    .filter_map(|&(x, y)| reflect(y, line, height).map(|y| (x, y)))
+                    .collect()
+            },
+            Instr::FoldX(line) => {
+                self.width = folded_size(line, width);
+                self.dots.iter()
+                    .filter_map(|&(x, y)| reflect(x, line, width).map(|x| (x, y)))
+                    .collect()
+            }
+        };
+    }
+
+    pub fn do_all_folds(&mut self) {
+        while !self.instructions.is_empty() {
+            self.do_fold()
+        }
+    }
+
+    pub fn count_dots(&self) -> usize {
+        self.dots.len()
+    }
+
+    /// The sheet as rows of dots, indexed `[y][x]`.
+    pub fn rows(&self) -> Vec<Vec<bool>> {
+        (0..self.height).map(|y| {
+            (0..self.width).map(|x| self.dots.contains(&(x, y))).collect()
+        }).collect()
+    }
+}
+
+/// The original grid-of-bools representation, kept as a reference for the
+/// sparse `Manual`.
+#[derive(Debug, Clone)]
+pub struct DenseManual {
+    grid: Vec<Vec<bool>>,
+    instructions: Vec<Instr>
+}
+
+/// The most cells a `DenseManual` will lay out. The real sheet has a little
+/// over a million.
+pub const MAX_DENSE_CELLS: usize = 10_000_000;
+
+impl TryFrom<&Manual> for DenseManual {
+    type Error = String;
+
+    fn try_from(manual: &Manual) -> Result<Self, Self::Error> {
+        if manual.width.saturating_mul(manual.height) > MAX_DENSE_CELLS {
+            return Err(format!("the sheet is {} by {}, which is too big to lay out as a grid", manual.width, manual.height));
+        }
+
+        let mut grid = vec![vec![false; manual.width]; manual.height];
+
+        for &(x, y) in &manual.dots {
+            grid[y][x] = true;
+        }
+
+        Ok(DenseManual { grid, instructions: manual.instructions.clone() })
+    }
+}
+
+impl DenseManual {
+    pub fn do_fold(&mut self) {
+        if self.instructions.is_empty() {
+            return;
+        }
+
+        let instr = self.instructions.remove(0);
+
+        let height = self.grid.len();
+        let width = self.grid.first().map_or(0, |row| row.len());
+
         match instr {
-            Instr::FoldY(split) => {
-                let mut top = self.grid[..split].to_vec();
-                let bottom = self.grid[split+1..].to_vec();
+            Instr::FoldY(line) => {
+                let mut folded = vec![vec![false; width]; folded_size(line, height)];
 
-                for (y, row) in bottom.iter().rev().enumerate() {
-                    for x in 0..row.len() {
-                        top[y][x] |= row[x];
+                for (y, row) in self.grid.iter().enumerate() {
+                    if let Some(y) = reflect(y, line, height) {
+                        for (x, &dot) in row.iter().enumerate() {
+                            folded[y][x] |= dot;
+                        }
                     }
                 }
 
-                self.grid = top;
+                self.grid = folded;
             },
-            Instr::FoldX(split) => {
-                let (mut left, right): (Vec<Vec<bool>>, Vec<Vec<bool>>) = self.grid.iter().map(|row| {
-                    let (l, r) = row.split_at(split);
-                    let r = &r[1..];
+            Instr::FoldX(line) => {
+                let mut folded = vec![vec![false; folded_size(line, width)]; height];
 
-                    (l.to_vec(), r.to_vec())
-                }).unzip();
-
-                let right_rev = right.iter().map(|row| {
-                    row.iter().rev().cloned().collect()
-                }).collect::<Vec<Vec<_>>>();
-
-                let max_x = right_rev.first().unwrap().len();
-
-                for y in 0..left.len() {
-                    for x in 0..max_x {
-                        left[y][x] |= right_rev[y][x];
+                for (y, row) in self.grid.iter().enumerate() {
+                    for (x, &dot) in row.iter().enumerate() {
+                        if let Some(x) = reflect(x, line, width) {
+                            folded[y][x] |= dot;
+                        }
                     }
                 }
 
-                self.grid = left;
+                self.grid = folded;
             }
         }
     }
 
-    fn do_all_folds(&mut self) {
+    pub fn do_all_folds(&mut self) {
         while !self.instructions.is_empty() {
             self.do_fold()
         }
     }
 
-    fn count_dots(&self) -> usize {
+    pub fn count_dots(&self) -> usize {
         self.grid.iter().map(|r| r.iter().filter(|&&e| e).count()).sum()
     }
-
-    fn print(&self) {
-        for row in &self.grid {
-            for elem in row {
-                if *elem {
-                    print!("#");
-                } else {
-                    print!(".");
-                }
-            }
-            println!("");
-        }
-    }
 }
 
 #[aoc(day13, part1)]
@@ -123,13 +279,202 @@ fn count_dots_after_first_fold(input: &Manual) -> usize {
     manual.count_dots()
 }
 
-#[aoc(day13, part2)]
-fn part2(input: &Manual) -> usize {
+#[aoc(day13, part1, dense)]
+fn count_dots_after_first_fold_dense(input: &Manual) -> Result<usize, String> {
+    let mut manual = DenseManual::try_from(input)?;
+    manual.do_fold();
+    Ok(manual.count_dots())
+}
+
+/// The most cells a fully folded sheet can have and still be read. The real
+/// one folds down to 40 by 6.
+pub const MAX_FOLDED_CELLS: usize = 100_000;
+
+fn folded_rows(input: &Manual) -> Result<Vec<Vec<bool>>, String> {
     let mut manual = input.clone();
 
     manual.do_all_folds();
 
-    manual.print();
+    if manual.width.saturating_mul(manual.height) > MAX_FOLDED_CELLS {
+        return Err(format!("the folded sheet is {} by {}, which is too big to read", manual.width, manual.height));
+    }
+
+    Ok(manual.rows())
+}
+
+#[aoc(day13, part2)]
+fn part2(input: &Manual) -> Result<String, String> {
+    folded_rows(input).map(|rows| ocr::read_letters(&rows))
+}
+
+fn part1_dense(input: &str) -> Result<Run, String> {
+    registry::solve_with(input, parse_input, count_dots_after_first_fold_dense)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = Manual;
+    type Part1 = usize;
+    type Part2 = Answer;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        Ok(count_dots_after_first_fold(input))
+    }
+
+    /// The letters the dots spell out, or the dots themselves if there's a
+    /// letter the font doesn't know.
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        let rows = folded_rows(input)?;
+        let letters = ocr::read_letters(&rows);
+
+        if letters.contains(ocr::UNKNOWN) {
+            Ok(Answer::Grid(ocr::render(&rows)))
+        } else {
+            Ok(Answer::Text(letters))
+        }
+    }
+
+    fn visual(part: u32, input: &Self::Input) -> Option<Vec<String>> {
+        match part {
+            2 => folded_rows(input).ok().map(|rows| ocr::render(&rows)),
+            _ => None
+        }
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 13,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[
+        Variant { part: 1, name: "dense", solver: part1_dense }
+    ],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(count_dots_after_first_fold(&parse_input(EXAMPLE).unwrap()), 17);
+        assert_eq!(count_dots_after_first_fold_dense(&parse_input(EXAMPLE).unwrap()), Ok(17));
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(count_dots_after_first_fold(&parse_input(puzzle_input!(13)).unwrap()), 743);
+        assert_eq!(count_dots_after_first_fold_dense(&parse_input(puzzle_input!(13)).unwrap()), Ok(743));
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(part2(&parse_input(puzzle_input!(13)).unwrap()), Ok("RCPLAKHL".to_string()));
+    }
+
+    #[test]
+    fn sheets_too_big_to_read_are_errors() {
+        let manual = parse_input("0,0\n999,999\n\nfold along x=1000").unwrap();
+
+        assert_eq!(part2(&manual), Err("the folded sheet is 1000 by 1000, which is too big to read".to_string()));
+        assert_eq!(Puzzle::visual(2, &manual), None);
+
+        let huge = parse_input("0,0
+4294967295,4294967295
+
+fold along x=1").unwrap();
+
+        assert_eq!(count_dots_after_first_fold(&huge), 2);
+        assert_eq!(
+            count_dots_after_first_fold_dense(&huge),
+            Err("the sheet is 4294967296 by 4294967296, which is too big to lay out as a grid".to_string())
+        );
+    }
+
+    #[test]
+    fn malformed_manuals_report_line_numbers() {
+        assert_eq!(parse_input("1,2\n3;4\n5,6\n\nfold along x=1").unwrap_err(), "line 2: couldn't parse dot: \"3;4\"");
+        assert_eq!(parse_input("1,2\n3,4\n\nfold along z=1").unwrap_err(), "line 4: expected axis 'x' or 'y': \"fold along z=1\"");
+        assert_eq!(parse_input("1,2\n\nfold along y=1\nfol").unwrap_err(), "line 4: expected 'fold': \"fol\"");
+        assert_eq!(parse_input("1,2\n\nfold along x=3 please").unwrap_err(), "line 3: unexpected characters after fold position: \"fold along x=3 please\"");
+        assert_eq!(parse_input("1,2\nfold along x=1").unwrap_err(), "missing blank line before fold instructions");
+        assert_eq!(parse_input("1,4294967296\n\nfold along x=1").unwrap_err(), "line 1: dot is too far out: \"1,4294967296\"");
+        assert_eq!(parse_input("1,2\n\nfold along x=4294967296").unwrap_err(), "line 3: fold position too large: \"fold along x=4294967296\"");
+
+        // every bad fold is reported, not just the first
+        assert_eq!(
+            parse_input("1,2\n3,4\n\nfold along x\nfold along y=2\nfold along y=").unwrap_err(),
+            "line 4: expected '=': \"fold along x\"\nline 6: expected a fold position: \"fold along y=\""
+        );
+    }
 
-    0
-}
\ No newline at end of file
+    /// Dots on a small sheet and a few folds, not necessarily down the middle.
+    fn manual_input() -> impl Strategy<Value = String> {
+        (prop::collection::vec((0..30usize, 0..30usize), 1..60),
+         prop::collection::vec((prop::bool::ANY, 0..30usize), 1..5))
+            .prop_map(|(dots, folds)| {
+                let dots = dots.iter().map(|(x, y)| format!("{},{}", x, y));
+                let folds = folds.iter().map(|&(x, line)| format!("fold along {}={}", if x { 'x' } else { 'y' }, line));
+
+                format!("{}\n\n{}", dots.collect::<Vec<_>>().join("\n"), folds.collect::<Vec<_>>().join("\n"))
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn folds_never_add_dots(input in manual_input()) {
+            let mut manual = parse_input(&input).unwrap();
+
+            while !manual.instructions.is_empty() {
+                let before = manual.count_dots();
+                manual.do_fold();
+
+                prop_assert!(manual.count_dots() <= before);
+            }
+        }
+
+        #[test]
+        fn sparse_and_dense_manuals_agree(input in manual_input()) {
+            let mut manual = parse_input(&input).unwrap();
+            let mut dense = DenseManual::try_from(&manual).unwrap();
+
+            while !manual.instructions.is_empty() {
+                manual.do_fold();
+                dense.do_fold();
+
+                prop_assert_eq!(&manual.rows(), &dense.grid);
+            }
+        }
+    }
+}
@@ -1,10 +1,15 @@
+//! Day 14: Extended Polymerization.
+
 use std::collections::HashMap;
 use std::str::FromStr;
-use aoc_runner_derive::aoc;
-use itertools::Itertools;
+use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day};
+use crate::Solution;
 
+/// A polymer template, which is never empty, and the rules for what to insert
+/// between each pair of elements. Pairs without a rule get nothing inserted.
 #[derive(Debug, Clone)]
-struct PolymerInstrs {
+pub struct PolymerInstrs {
     template: Vec<char>,
     pair_rules: HashMap<(char, char), char>
 }
@@ -16,6 +21,10 @@ impl FromStr for PolymerInstrs {
         let (tpl, rules) = s.split_once("\n\n").ok_or("couldn't split template")?;
 
         let template = tpl.chars().collect::<Vec<_>>();
+        if template.is_empty() {
+            return Err("template is empty");
+        }
+
         let pair_rules = rules.lines().map(|line| {
             let (pair, result) = line.split_once(" -> ").ok_or("couldn't split rule")?;
 
@@ -39,26 +48,20 @@ impl FromStr for PolymerInstrs {
 }
 
 impl PolymerInstrs {
-    fn step(&mut self) {
-        let new_entries = self.template.windows(2).map(|window| {
-            match window {
-                &[a, b] => {
-                    self.pair_rules.get(&(a, b)).unwrap()
-                },
-                _ => panic!("unexpected window size")
-            }
-        });
+    pub fn step(&mut self) {
+        let mut new_data = Vec::with_capacity(self.template.len() * 2);
 
-        let new_data = self.template.iter()
-            .interleave(new_entries)
-            .cloned()
-            .collect::<Vec<_>>();
+        for window in self.template.windows(2) {
+            new_data.push(window[0]);
+            new_data.extend(self.pair_rules.get(&(window[0], window[1])));
+        }
 
+        new_data.extend(self.template.last());
 
         self.template = new_data;
     }
 
-    fn occurrences(&self) -> HashMap<char, usize> {
+    pub fn occurrences(&self) -> HashMap<char, usize> {
         let mut counts = HashMap::new();
 
         for e in &self.template {
@@ -69,9 +72,14 @@ impl PolymerInstrs {
     }
 }
 
+#[aoc_generator(day14)]
+fn parse_input(input: &str) -> Result<PolymerInstrs, &'static str> {
+    input.parse()
+}
+
 #[aoc(day14, part1)]
-fn answer_1(input: &str) -> usize {
-    let mut instrs = input.parse::<PolymerInstrs>().unwrap();
+fn answer_1(input: &PolymerInstrs) -> usize {
+    let mut instrs = input.clone();
 
     for _ in 0..10 {
         instrs.step();
@@ -82,45 +90,51 @@ fn answer_1(input: &str) -> usize {
     occurrences.values().max().unwrap() - occurrences.values().min().unwrap()
 }
 
+/// Tracks how many times each adjacent pair occurs instead of the polymer
+/// itself, which grows exponentially.
 #[derive(Debug, Clone)]
-struct PolymerInstrs2 {
+pub struct PolymerInstrs2 {
     template: Vec<char>,
     pair_rules: HashMap<(char, char), char>,
     pair_counts: HashMap<(char, char), usize>
 }
 
 impl PolymerInstrs2 {
-    fn new(input: &str) -> Self {
-        let instrs = input.parse::<PolymerInstrs>().unwrap();
+    pub fn new(instrs: &PolymerInstrs) -> Self {
+        let mut pair_counts = HashMap::new();
 
-        let pair_counts = instrs.template.windows(2).map(|window| {
+        // a pair can occur more than once in the template
+        for window in instrs.template.windows(2) {
             match window {
-                &[a, b] => ((a, b), 1),
+                &[a, b] => *pair_counts.entry((a, b)).or_insert(0) += 1,
                 _ => panic!("unexpected window size")
             }
-        }).collect::<HashMap<(char, char), usize>>();
+        }
 
         PolymerInstrs2 {
-            template: instrs.template,
-            pair_rules: instrs.pair_rules,
+            template: instrs.template.clone(),
+            pair_rules: instrs.pair_rules.clone(),
             pair_counts
         }
     }
 
-    fn step(&mut self) {
+    pub fn step(&mut self) {
         let mut new_counts = HashMap::new();
 
         for (&(a, b), &count) in self.pair_counts.iter() {
-            let &result = self.pair_rules.get(&(a, b)).unwrap();
-
-            *new_counts.entry((a, result)).or_insert(0) += count;
-            *new_counts.entry((result, b)).or_insert(0) += count;
+            match self.pair_rules.get(&(a, b)) {
+                Some(&result) => {
+                    *new_counts.entry((a, result)).or_insert(0) += count;
+                    *new_counts.entry((result, b)).or_insert(0) += count;
+                },
+                None => *new_counts.entry((a, b)).or_insert(0) += count
+            }
         }
 
         self.pair_counts = new_counts;
     }
 
-    fn occurrences(&self) -> HashMap<char, usize> {
+    pub fn occurrences(&self) -> HashMap<char, usize> {
         let mut counts = HashMap::new();
 
         for (&(a, _), &count) in self.pair_counts.iter() {
@@ -134,11 +148,10 @@ impl PolymerInstrs2 {
 }
 
 #[aoc(day14, part2)]
-fn answer_2(input: &str) -> usize {
+fn answer_2(input: &PolymerInstrs) -> usize {
     let mut instrs = PolymerInstrs2::new(input);
 
-    for n in 0..40 {
-        println!("iteration {}", n);
+    for _ in 0..40 {
         instrs.step();
     }
 
@@ -146,3 +159,123 @@ fn answer_2(input: &str) -> usize {
 
     occurrences.values().max().unwrap() - occurrences.values().min().unwrap()
 }
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = PolymerInstrs;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_input(input).map_err(|e| e.to_string())
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        Ok(answer_1(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        Ok(answer_2(input))
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 14,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(answer_1(&parse_input(EXAMPLE).unwrap()), 1588);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(answer_2(&parse_input(EXAMPLE).unwrap()), 2188189693529);
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(answer_1(&parse_input(puzzle_input!(14)).unwrap()), 2435);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(answer_2(&parse_input(puzzle_input!(14)).unwrap()), 2587447599164);
+    }
+
+    #[test]
+    fn pairs_without_rules_are_left_alone() {
+        // only AC has a rule, and once it's split nothing else can grow
+        let instrs = parse_input("ABAC\n\nAC -> B").unwrap();
+        let mut stepped = instrs.clone();
+        stepped.step();
+        stepped.step();
+
+        assert_eq!(stepped.template, "ABABC".chars().collect::<Vec<_>>());
+        assert_eq!(answer_1(&instrs), 1);
+        assert_eq!(answer_2(&instrs), 1);
+
+        assert_eq!(answer_1(&parse_input("A\n\nAA -> B").unwrap()), 0);
+        assert_eq!(parse_input("\n\nAA -> B").unwrap_err(), "template is empty");
+    }
+
+    /// A template over a few elements and a rule for every pair of them.
+    fn polymer_input() -> impl Strategy<Value = String> {
+        const ELEMENTS: [char; 3] = ['A', 'B', 'C'];
+
+        (prop::collection::vec(prop::sample::select(&ELEMENTS[..]), 2..12),
+         prop::collection::vec(prop::sample::select(&ELEMENTS[..]), 9))
+            .prop_map(|(template, results)| {
+                let pairs = ELEMENTS.iter().flat_map(|&a| ELEMENTS.iter().map(move |&b| (a, b)));
+                let rules = pairs.zip(results).map(|((a, b), r)| format!("{}{} -> {}", a, b, r));
+
+                format!("{}\n\n{}", template.iter().collect::<String>(), rules.collect::<Vec<_>>().join("\n"))
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn pair_counts_match_naive_polymer(input in polymer_input(), steps in 0..6usize) {
+            let mut naive = parse_input(&input).unwrap();
+            let mut pairs = PolymerInstrs2::new(&naive);
+
+            for _ in 0..steps {
+                naive.step();
+                pairs.step();
+            }
+
+            prop_assert_eq!(naive.occurrences(), pairs.occurrences());
+        }
+    }
+}
@@ -1,89 +1,631 @@
+//! Day 2: Dive!.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
 use std::str::FromStr;
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day};
+use crate::Solution;
+use yap::{IntoTokens, Tokens};
 
-#[derive(PartialEq, Debug)]
-enum Cmd {
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Cmd {
     Forward(u32),
+    Back(u32),
     Up(u32),
     Down(u32)
 }
 
+const KEYWORDS: &[&str] = &["forward", "back", "up", "down", "repeat", "macro"];
+
+/// A few bytes of nested `repeat`s and macros can describe more commands than
+/// fit in memory, so no script may expand to more than this, counting the
+/// commands held in macro and `repeat` bodies along the way.
+pub const MAX_COMMANDS: usize = 10_000_000;
+
+fn skip_spaces(t: &mut impl Tokens<Item=char>) -> usize {
+    t.skip_tokens_while(|&c| c == ' ' || c == '\t')
+}
+
+/// Skips whitespace, blank lines and `#` comments.
+fn skip_blank(t: &mut impl Tokens<Item=char>) {
+    loop {
+        t.skip_tokens_while(|c| c.is_whitespace());
+
+        if !t.token('#') {
+            break;
+        }
+
+        t.skip_tokens_while(|&c| c != '\n');
+    }
+}
+
+fn parse_word(t: &mut impl Tokens<Item=char>) -> String {
+    t.tokens_while(|c| c.is_ascii_alphanumeric() || *c == '_').collect()
+}
+
+fn parse_number(t: &mut impl Tokens<Item=char>) -> Result<u32, &'static str> {
+    if skip_spaces(t) == 0 {
+        return Err("expected a number");
+    }
+
+    let digits = t.tokens_while(|c| c.is_ascii_digit()).collect::<String>();
+    if digits.is_empty() {
+        return Err("expected a number");
+    }
+
+    digits.parse().map_err(|_| "number too large")
+}
+
+fn parse_cmd(word: &str, t: &mut impl Tokens<Item=char>) -> Option<Result<Cmd, &'static str>> {
+    let cmd: fn(u32) -> Cmd = match word {
+        "forward" => Cmd::Forward,
+        "back" => Cmd::Back,
+        "up" => Cmd::Up,
+        "down" => Cmd::Down,
+        _ => return None
+    };
+
+    Some(parse_number(t).map(cmd))
+}
+
 impl FromStr for Cmd {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cmd_pair = s.trim().split(" ").collect::<Vec<&str>>();
+        let mut t = s.trim().into_tokens();
+        let word = parse_word(&mut t);
+        let cmd = parse_cmd(&word, &mut t).ok_or("unrecognized cmd")??;
 
-        match cmd_pair[..] {
-            ["forward", n] => Ok(Cmd::Forward(n.parse().unwrap())),
-            ["up", n] => Ok(Cmd::Up(n.parse().unwrap())),
-            ["down", n] => Ok(Cmd::Down(n.parse().unwrap())),
-            _ => Err("unrecognized cmd")
+        if t.peek().is_some() {
+            return Err("unexpected characters after cmd");
+        }
+
+        Ok(cmd)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: &'static str
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Expands a script into the commands it runs. Besides one command per line, a
+/// script may contain blank lines, `#` comments, `repeat N { ... }` blocks and
+/// `macro name { ... }` definitions, after which `name` runs the block. Blocks
+/// can hold several commands on one line, and a macro can only use macros
+/// defined before it. Scripts that expand to more than [`MAX_COMMANDS`]
+/// commands are errors.
+pub fn parse_script(input: &str) -> Result<Vec<Cmd>, ScriptError> {
+    let mut t = input.into_tokens();
+    let mut scope = Scope { macros: HashMap::new(), budget: MAX_COMMANDS };
+    let mut cmds = vec![];
+
+    parse_block(&mut t, &mut scope, &mut cmds, false).map_err(|(offset, message)| {
+        ScriptError { line: input[..offset].matches('\n').count() + 1, message }
+    })?;
+
+    Ok(cmds)
+}
+
+type BlockError = (usize, &'static str);
+
+/// The macros defined so far, and how many more commands the script may expand
+/// to. Every command the script expands to counts, whether it ends up in the
+/// script, a macro or a `repeat` body.
+struct Scope {
+    macros: HashMap<String, Vec<Cmd>>,
+    budget: usize
+}
+
+/// Appends `times` copies of `body` to `cmds`, as long as `budget` allows.
+fn expand(budget: &mut usize, cmds: &mut Vec<Cmd>, body: &[Cmd], times: u32, at: usize) -> Result<(), BlockError> {
+    match body.len().checked_mul(times as usize) {
+        Some(total) if total <= *budget => *budget -= total,
+        _ => return Err((at, "script expands to too many commands"))
+    }
+
+    for _ in 0..times {
+        cmds.extend_from_slice(body);
+    }
+
+    Ok(())
+}
+
+fn parse_body(t: &mut impl Tokens<Item=char>, scope: &mut Scope) -> Result<Vec<Cmd>, BlockError> {
+    skip_blank(t);
+    if !t.token('{') {
+        return Err((t.offset(), "expected '{'"));
+    }
+
+    let mut body = vec![];
+    parse_block(t, scope, &mut body, true)?;
+
+    Ok(body)
+}
+
+fn parse_block(
+    t: &mut impl Tokens<Item=char>,
+    scope: &mut Scope,
+    cmds: &mut Vec<Cmd>,
+    nested: bool
+) -> Result<(), BlockError> {
+    loop {
+        skip_blank(t);
+        let at = t.offset();
+
+        match t.peek() {
+            None if nested => return Err((at, "unclosed '{'")),
+            None => return Ok(()),
+            Some('}') if nested => {
+                t.next();
+                return Ok(());
+            },
+            Some('}') => return Err((at, "unexpected '}'")),
+            _ => {}
+        }
+
+        let word = parse_word(t);
+
+        if let Some(cmd) = parse_cmd(&word, t) {
+            expand(&mut scope.budget, cmds, &[cmd.map_err(|e| (at, e))?], 1, at)?;
+            continue;
+        }
+
+        match word.as_str() {
+            "" => return Err((at, "unexpected character")),
+            "repeat" => {
+                let n = parse_number(t).map_err(|e| (at, e))?;
+                let body = parse_body(t, scope)?;
+                expand(&mut scope.budget, cmds, &body, n, at)?;
+            },
+            "macro" => {
+                if skip_spaces(t) == 0 {
+                    return Err((at, "expected a macro name"));
+                }
+
+                let name = parse_word(t);
+                if name.is_empty() || KEYWORDS.contains(&name.as_str()) {
+                    return Err((at, "expected a macro name"));
+                }
+
+                let body = parse_body(t, scope)?;
+                scope.macros.insert(name, body);
+            },
+            name => {
+                let body = scope.macros.get(name).ok_or((at, "unrecognized cmd"))?;
+                expand(&mut scope.budget, cmds, body, 1, at)?;
+            }
         }
     }
 }
 
 #[aoc_generator(day2)]
-fn parse_cmds(input: &str) -> Vec<Cmd> {
-    input.lines().map(|line| line.parse().unwrap()).collect()
+fn parse_cmds(input: &str) -> Result<Vec<Cmd>, ScriptError> {
+    parse_script(input)
+}
+
+/// A submarine model that `Cmd`s can steer. Moves return `None` if they would
+/// overflow.
+pub trait Submarine {
+    fn make_move(&mut self, cmd: &Cmd) -> Option<()>;
+    fn horizontal(&self) -> i64;
+    fn depth(&self) -> i64;
+
+    fn aim(&self) -> i64 {
+        0
+    }
+
+    fn state(&self) -> State {
+        State { x: self.horizontal(), depth: self.depth(), aim: self.aim() }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct State {
+    pub x: i64,
+    pub depth: i64,
+    pub aim: i64
+}
+
+/// Yields the submarine's state after each command, or an error for the first
+/// command that overflows, after which it stops.
+pub struct Trace<'a, S> {
+    sub: S,
+    cmds: std::iter::Enumerate<std::slice::Iter<'a, Cmd>>,
+    failed: bool
+}
+
+pub fn trace<S: Submarine>(sub: S, cmds: &[Cmd]) -> Trace<'_, S> {
+    Trace { sub, cmds: cmds.iter().enumerate(), failed: false }
+}
+
+impl<'a, S: Submarine> Iterator for Trace<'a, S> {
+    type Item = Result<State, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let (i, cmd) = self.cmds.next()?;
+
+        if self.sub.make_move(cmd).is_none() {
+            self.failed = true;
+            return Some(Err(format!("arithmetic overflow at command {}: {:?}", i + 1, cmd)));
+        }
+
+        Some(Ok(self.sub.state()))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TraceSummary {
+    pub max_depth: i64,
+    /// The command (counting from 1) that first reached `max_depth`, or 0 if the
+    /// submarine never got deeper than where it started.
+    pub max_depth_step: usize,
+    /// Horizontal plus vertical distance covered, summed over every command.
+    pub total_distance: u64
+}
+
+impl<'a, S: Submarine> Trace<'a, S> {
+    pub fn summary(self) -> Result<TraceSummary, String> {
+        let start = self.sub.state();
+        let mut summary = TraceSummary { max_depth: start.depth, max_depth_step: 0, total_distance: 0 };
+        let mut last = start;
+
+        for (i, state) in self.enumerate() {
+            let state = state?;
+
+            if state.depth > summary.max_depth {
+                summary.max_depth = state.depth;
+                summary.max_depth_step = i + 1;
+            }
+
+            summary.total_distance = summary.total_distance
+                .checked_add(state.x.abs_diff(last.x))
+                .and_then(|d| d.checked_add(state.depth.abs_diff(last.depth)))
+                .ok_or("total distance overflows")?;
+
+            last = state;
+        }
+
+        Ok(summary)
+    }
+
+    /// Writes the starting state and the state after each command as
+    /// `step,x,depth,aim` rows.
+    pub fn write_csv<W: Write>(self, w: &mut W) -> Result<(), String> {
+        let start = self.sub.state();
+
+        writeln!(w, "step,x,depth,aim").map_err(|e| e.to_string())?;
+        writeln!(w, "0,{},{},{}", start.x, start.depth, start.aim).map_err(|e| e.to_string())?;
+
+        for (i, state) in self.enumerate() {
+            let state = state?;
+            writeln!(w, "{},{},{},{}", i + 1, state.x, state.depth, state.aim).map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Where a submarine ended up, and the first command (counting from 1) after
+/// which it was above the surface, if it ever was.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Navigation {
+    pub x: i64,
+    pub depth: i64,
+    pub surfaced_at: Option<usize>
+}
+
+impl Navigation {
+    pub fn product(&self) -> Option<i64> {
+        self.x.checked_mul(self.depth)
+    }
+}
+
+pub fn navigate<S: Submarine>(sub: S, cmds: &[Cmd]) -> Result<Navigation, String> {
+    let mut last = sub.state();
+    let mut surfaced_at = None;
+
+    for (i, state) in trace(sub, cmds).enumerate() {
+        last = state?;
+
+        if surfaced_at.is_none() && last.depth < 0 {
+            surfaced_at = Some(i + 1);
+        }
+    }
+
+    Ok(Navigation { x: last.x, depth: last.depth, surfaced_at })
 }
 
-struct Position {
-    x: u32,
-    y: u32
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Position {
+    x: i64,
+    y: i64
 }
 
 impl Position {
-    fn init() -> Self {
+    pub fn init() -> Self {
         Position{ x: 0, y: 0 }
     }
 }
 
-#[aoc(day2, part1)]
-fn final_position_product(cmds: &[Cmd]) -> u32 {
-    let mut pos = Position::init();
-
-    for cmd in cmds {
-        match cmd {
-            Cmd::Forward(k) => pos.x += k,
-            Cmd::Down(k) => pos.y += k,
-            Cmd::Up(k) => pos.y -= k
+impl Submarine for Position {
+    fn make_move(&mut self, cmd: &Cmd) -> Option<()> {
+        match *cmd {
+            Cmd::Forward(k) => self.x = self.x.checked_add(k.into())?,
+            Cmd::Back(k) => self.x = self.x.checked_sub(k.into())?,
+            Cmd::Down(k) => self.y = self.y.checked_add(k.into())?,
+            Cmd::Up(k) => self.y = self.y.checked_sub(k.into())?
         }
+
+        Some(())
+    }
+
+    fn horizontal(&self) -> i64 {
+        self.x
     }
 
-    pos.y * pos.x
+    fn depth(&self) -> i64 {
+        self.y
+    }
 }
 
-struct Vector {
-    x: u32,
-    y: u32,
-    aim: u32
+#[aoc(day2, part1)]
+fn final_position_product(cmds: &[Cmd]) -> Result<i64, String> {
+    navigate(Position::init(), cmds)?.product().ok_or_else(|| "product overflows".to_string())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Vector {
+    x: i64,
+    y: i64,
+    aim: i64
 }
 
 impl Vector {
-    fn init() -> Self {
+    pub fn init() -> Self {
         Vector{ x: 0, y: 0, aim: 0 }
     }
+}
 
-    fn make_move(&mut self, cmd: &Cmd) {
-        match cmd {
-            Cmd::Up(u) => self.aim -= u,
-            Cmd::Down(d) => self.aim += d,
+impl Submarine for Vector {
+    fn make_move(&mut self, cmd: &Cmd) -> Option<()> {
+        match *cmd {
+            Cmd::Up(u) => self.aim = self.aim.checked_sub(u.into())?,
+            Cmd::Down(d) => self.aim = self.aim.checked_add(d.into())?,
             Cmd::Forward(f) => {
-                self.x += f;
-                self.y += self.aim * f;
+                self.x = self.x.checked_add(f.into())?;
+                self.y = self.y.checked_add(self.aim.checked_mul(f.into())?)?;
+            },
+            Cmd::Back(b) => {
+                self.x = self.x.checked_sub(b.into())?;
+                self.y = self.y.checked_sub(self.aim.checked_mul(b.into())?)?;
             }
         }
+
+        Some(())
+    }
+
+    fn horizontal(&self) -> i64 {
+        self.x
+    }
+
+    fn depth(&self) -> i64 {
+        self.y
+    }
+
+    fn aim(&self) -> i64 {
+        self.aim
     }
 }
 
 #[aoc(day2, part2)]
-fn final_position_product_with_aim(cmds: &[Cmd]) -> u32 {
-    let mut vec = Vector::init();
+fn final_position_product_with_aim(cmds: &[Cmd]) -> Result<i64, String> {
+    navigate(Vector::init(), cmds)?.product().ok_or_else(|| "product overflows".to_string())
+}
+
+pub struct Puzzle;
 
-    for cmd in cmds {
-        vec.make_move(cmd);
+impl Solution for Puzzle {
+    type Input = Vec<Cmd>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_script(input).map_err(|e| e.to_string())
     }
 
-    vec.x * vec.y
-}
\ No newline at end of file
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        final_position_product(input)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        final_position_product_with_aim(input)
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 2,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(final_position_product(&parse_cmds(EXAMPLE).unwrap()).unwrap(), 150);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(final_position_product_with_aim(&parse_cmds(EXAMPLE).unwrap()).unwrap(), 900);
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(final_position_product(&parse_cmds(puzzle_input!(2)).unwrap()).unwrap(), 1813801);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(final_position_product_with_aim(&parse_cmds(puzzle_input!(2)).unwrap()).unwrap(), 1960569556);
+    }
+
+    #[test]
+    fn records_when_the_submarine_surfaces() {
+        let cmds = parse_cmds("down 2\nforward 1\nup 3\ndown 5").unwrap();
+
+        assert_eq!(navigate(Position::init(), &cmds).unwrap(), Navigation { x: 1, depth: 4, surfaced_at: Some(3) });
+        assert_eq!(navigate(Position::init(), &parse_cmds(EXAMPLE).unwrap()).unwrap().surfaced_at, None);
+    }
+
+    #[test]
+    fn overflows_are_errors() {
+        let cmds = parse_cmds("down 4294967295\nforward 4294967295").unwrap();
+
+        assert_eq!(
+            navigate(Vector::init(), &cmds),
+            Err("arithmetic overflow at command 2: Forward(4294967295)".to_string())
+        );
+        assert_eq!(final_position_product(&parse_cmds("forward 4294967295\ndown 4294967295").unwrap()), Err("product overflows".to_string()));
+    }
+
+    #[test]
+    fn scripts_expand_macros_and_repeats() {
+        let script = "\
+# warm up
+forward 3
+back 1
+macro dive { down 2 forward 1 }
+repeat 2 {
+    dive
+}
+up 1 # nearly there
+repeat 0 { forward 100 }
+";
+
+        assert_eq!(parse_script(script).unwrap(), vec![
+            Cmd::Forward(3), Cmd::Back(1),
+            Cmd::Down(2), Cmd::Forward(1), Cmd::Down(2), Cmd::Forward(1),
+            Cmd::Up(1)
+        ]);
+    }
+
+    #[test]
+    fn script_errors_have_line_numbers() {
+        let error = |script| parse_script(script).unwrap_err();
+
+        assert_eq!(error("forward 1\n\n# turn\nsideways 2"), ScriptError { line: 4, message: "unrecognized cmd" });
+        assert_eq!(error("forward 1\ndown"), ScriptError { line: 2, message: "expected a number" });
+        assert_eq!(error("macro repeat { forward 1 }"), ScriptError { line: 1, message: "expected a macro name" });
+        assert_eq!(error("macro up { forward 1 }"), ScriptError { line: 1, message: "expected a macro name" });
+        assert_eq!(error("dive\nmacro dive { down 1 }"), ScriptError { line: 1, message: "unrecognized cmd" });
+        assert_eq!(error("repeat 2 {\n  forward 1\n"), ScriptError { line: 3, message: "unclosed '{'" });
+        assert_eq!(error("forward 1\n}"), ScriptError { line: 2, message: "unexpected '}'" });
+        assert_eq!(error("forward 1\nrepeat 2 forward 1"), ScriptError { line: 2, message: "expected '{'" });
+    }
+
+    #[test]
+    fn scripts_cannot_expand_without_limit() {
+        assert_eq!(
+            parse_script("repeat 4000000000 { forward 1 }").unwrap_err(),
+            ScriptError { line: 1, message: "script expands to too many commands" }
+        );
+        assert_eq!(
+            parse_script("macro a { forward 1 forward 1 }\nrepeat 10000 {\n  repeat 10000 { a }\n}").unwrap_err(),
+            ScriptError { line: 2, message: "script expands to too many commands" }
+        );
+        assert_eq!(parse_script("repeat 1000 { repeat 1000 { up 1 } }").unwrap().len(), 1_000_000);
+    }
+
+    #[test]
+    fn macros_share_the_expansion_limit() {
+        // each macro is well within the limit, but together they aren't
+        let script = (1..=8).map(|i| format!("macro m{} {{ repeat 2000000 {{ forward 1 }} }}", i)).collect::<Vec<_>>();
+
+        assert_eq!(
+            parse_script(&script.join("\n")).unwrap_err(),
+            ScriptError { line: 5, message: "script expands to too many commands" }
+        );
+        assert!(parse_script(&script[..4].join("\n")).is_ok());
+    }
+
+    #[test]
+    fn traces_the_example_course() {
+        let cmds = parse_cmds(EXAMPLE).unwrap();
+        let state = |x, depth, aim| State { x, depth, aim };
+
+        assert_eq!(trace(Vector::init(), &cmds).collect::<Result<Vec<_>, _>>().unwrap(), vec![
+            state(5, 0, 0),
+            state(5, 0, 5),
+            state(13, 40, 5),
+            state(13, 40, 2),
+            state(13, 40, 10),
+            state(15, 60, 10)
+        ]);
+
+        assert_eq!(
+            trace(Vector::init(), &cmds).summary().unwrap(),
+            TraceSummary { max_depth: 60, max_depth_step: 6, total_distance: 75 }
+        );
+        assert_eq!(
+            trace(Position::init(), &cmds).summary().unwrap(),
+            TraceSummary { max_depth: 10, max_depth_step: 5, total_distance: 31 }
+        );
+    }
+
+    #[test]
+    fn writes_the_trace_as_csv() {
+        let mut csv = vec![];
+        trace(Vector::init(), &parse_cmds(EXAMPLE).unwrap()).write_csv(&mut csv).unwrap();
+
+        assert_eq!(String::from_utf8(csv).unwrap(), "\
+step,x,depth,aim
+0,0,0,0
+1,5,0,0
+2,5,0,5
+3,13,40,5
+4,13,40,2
+5,13,40,10
+6,15,60,10
+");
+    }
+
+    #[test]
+    fn traces_stop_at_the_first_overflow() {
+        let cmds = parse_cmds("down 4294967295\nforward 4294967295\nforward 1").unwrap();
+        let states = trace(Vector::init(), &cmds).collect::<Vec<_>>();
+
+        assert_eq!(states.len(), 2);
+        assert!(states[1].is_err());
+        assert!(trace(Vector::init(), &cmds).summary().is_err());
+    }
+}
@@ -1,116 +1,271 @@
-use std::collections::HashMap;
+//! Day 3: Binary Diagnostic.
+
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day};
+use crate::Solution;
 
-#[aoc_generator(day3)]
-fn parse_bool_vecs(input: &str) -> Vec<Vec<bool>> {
-    input.lines().map(|line| {
-        line.chars().map(|char| match char {
-            '0' => false,
-            '1' => true,
-            _ => panic!("unexpected char")
-        }).collect()
-    }).collect()
+/// What counts as the most common bit when a column has as many ones as zeros.
+/// The least common bit is always the other one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TieBreak {
+    PreferOne,
+    PreferZero,
+    Error
 }
 
-struct BitCounts {
-    zero_counts: HashMap<usize, u32>,
-    one_counts: HashMap<usize, u32>
-}
+impl TieBreak {
+    /// How part 1 breaks ties, which the puzzle leaves open: a tied column
+    /// counts as mostly zeros.
+    pub const PART1: TieBreak = TieBreak::PreferZero;
 
-fn bit_counts(input: &[Vec<bool>]) -> BitCounts {
-    let mut count_zeroes: HashMap<usize, u32> = HashMap::new();
-    let mut count_ones: HashMap<usize, u32> = HashMap::new();
+    /// The puzzle's rule for part 2: a tied column keeps the rows with a 1 for
+    /// the oxygen rating, and those with a 0 for the CO2 rating.
+    pub const PART2: TieBreak = TieBreak::PreferOne;
 
-    for row in input {
-        for (i , &elem) in row.iter().enumerate() {
-            let entry = if elem {
-                count_ones.entry(i).or_insert(0)
-            } else {
-                count_zeroes.entry(i).or_insert(0)
-            };
+    fn most_common(&self, ones: usize, zeros: usize, bit: usize) -> Result<bool, String> {
+        if ones != zeros {
+            return Ok(ones > zeros);
+        }
 
-            *entry += 1;
+        match self {
+            TieBreak::PreferOne => Ok(true),
+            TieBreak::PreferZero => Ok(false),
+            TieBreak::Error => Err(format!("bit {} is tied at {} ones and {} zeros", bit, ones, zeros))
         }
     }
+}
 
-    BitCounts{ zero_counts: count_zeroes, one_counts: count_ones }
+/// Diagnostic rows packed into integers, with bit `width - 1` holding the
+/// leftmost column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    width: usize,
+    rows: Vec<u128>
 }
 
-fn shift_and_combine(v: &[u32]) -> u32 {
-    let mut acc = 0;
-    for (i, &v) in v.iter().rev().enumerate() {
-        acc += v * (1 << i)
+impl Report {
+    pub const MAX_WIDTH: usize = u128::BITS as usize;
+
+    pub fn new(width: usize, rows: Vec<u128>) -> Result<Self, &'static str> {
+        if width == 0 || width > Report::MAX_WIDTH {
+            return Err("width must be between 1 and 128 bits");
+        }
+
+        if rows.is_empty() {
+            return Err("report has no rows");
+        }
+
+        if rows.iter().any(|&r| width < Report::MAX_WIDTH && r >> width != 0) {
+            return Err("row wider than the declared width");
+        }
+
+        Ok(Report { width, rows })
+    }
+
+    fn mask(&self) -> u128 {
+        u128::MAX >> (Report::MAX_WIDTH - self.width)
     }
 
-    acc
+    fn one_count(&self, bit: usize) -> usize {
+        self.rows.iter().map(|&r| (r >> bit & 1) as usize).sum()
+    }
+
+    pub fn power_consumption(&self, tie: TieBreak) -> Result<u128, String> {
+        let mut gamma = 0;
+
+        for i in 0..self.width {
+            let ones = self.one_count(i);
+
+            if tie.most_common(ones, self.rows.len() - ones, i)? {
+                gamma |= 1 << i;
+            }
+        }
+
+        let epsilon = !gamma & self.mask();
+
+        gamma.checked_mul(epsilon).ok_or_else(|| "power consumption overflows".to_string())
+    }
+
+    pub fn life_support_rating(&self, tie: TieBreak) -> Result<u128, String> {
+        let mut sorted = self.rows.clone();
+        sorted.sort_unstable();
+
+        let oxygen = filter_common(true, &sorted, self.width, tie)?;
+        let co2 = filter_common(false, &sorted, self.width, tie)?;
+
+        oxygen.checked_mul(co2).ok_or_else(|| "life support rating overflows".to_string())
+    }
 }
 
-fn boolvec_to_int(bv: &[bool]) -> u32 {
-    shift_and_combine(&bv.iter().map(|&b| if b { 1 } else { 0 }).collect::<Vec<u32>>())
+fn bit(row: u128, i: usize) -> bool {
+    row >> i & 1 == 1
+}
+
+#[aoc_generator(day3)]
+fn parse_report(input: &str) -> Result<Report, String> {
+    let width = input.lines().next().map_or(0, |l| l.len());
+
+    let rows = input.lines().enumerate().map(|(i, line)| {
+        if line.len() != width {
+            return Err(format!("line {} has {} bits but line 1 has {}", i + 1, line.len(), width));
+        }
+
+        if !line.bytes().all(|b| b == b'0' || b == b'1') {
+            return Err(format!("line {}: not a binary number: {:?}", i + 1, line));
+        }
+
+        u128::from_str_radix(line, 2).map_err(|_| format!("line {}: wider than {} bits", i + 1, Report::MAX_WIDTH))
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    Report::new(width, rows).map_err(|e| e.to_string())
 }
 
 #[aoc(day3, part1)]
-fn power_consumption(input: &[Vec<bool>]) -> u32 {
-    let counts = bit_counts(input);
-    let row_len = input.first().unwrap().len();
-
-    let mut most_common: Vec<u32> = Vec::new();
-    for i in 0..row_len {
-        if counts.one_counts.get(&i).unwrap() > counts.zero_counts.get(&i).unwrap() {
-            most_common.push(1);
-        } else {
-            most_common.push(0);
+fn power_consumption(report: &Report) -> Result<u128, String> {
+    report.power_consumption(TieBreak::PART1)
+}
+
+/// Narrows a sorted range of rows bit by bit, from the most significant down,
+/// to the single row the bit criteria select. Rows in a range share every bit
+/// above the one being looked at, so rows with a 0 there all sort before rows
+/// with a 1 and each step is one binary search. A column where every remaining
+/// row agrees is skipped rather than emptying the range.
+fn filter_common(most_common: bool, sorted: &[u128], width: usize, tie: TieBreak) -> Result<u128, String> {
+    let mut range = sorted;
+
+    for i in (0..width).rev() {
+        if range.len() <= 1 {
+            break;
         }
-    }
 
-    let mut least_common: Vec<u32> = Vec::new();
-    for i in 0..row_len {
-        if counts.one_counts.get(&i).unwrap() > counts.zero_counts.get(&i).unwrap() {
-            least_common.push(0);
-        } else {
-            least_common.push(1);
+        let split = range.partition_point(|&r| !bit(r, i));
+        let (zeros, ones) = range.split_at(split);
+
+        if zeros.is_empty() || ones.is_empty() {
+            continue;
         }
+
+        let keep_ones = most_common == tie.most_common(ones.len(), zeros.len(), i)?;
+
+        range = if keep_ones { ones } else { zeros };
     }
 
-    shift_and_combine(&most_common[..]) * shift_and_combine(&least_common[..])
+    range.first().cloned().ok_or_else(|| "report has no rows".to_string())
 }
 
-fn filter_common(most_common: bool, input: &[Vec<bool>], i: usize) -> Vec<Vec<bool>> {
-    let mut count_zeroes = 0;
-    let mut count_ones = 0;
+#[aoc(day3, part2)]
+fn life_support_rating(report: &Report) -> Result<u128, String> {
+    report.life_support_rating(TieBreak::PART2)
+}
 
-    for row in input {
-        if *row.get(i).unwrap() {
-            count_ones += 1;
-        } else {
-            count_zeroes += 1;
-        }
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = Report;
+    type Part1 = u128;
+    type Part2 = u128;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_report(input)
     }
 
-    let looking_for = most_common == (count_ones >= count_zeroes);
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        power_consumption(input)
+    }
 
-    input.iter().filter(|&row| row.get(i).unwrap() == &looking_for).cloned().collect()
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        life_support_rating(input)
+    }
 }
 
-#[aoc(day3, part2)]
-fn life_support_rating(input: &[Vec<bool>]) -> u32 {
-    let row_len = input.first().unwrap().len();
+pub const DAY: Day = Day {
+    day: 3,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010
+";
 
-    let mut i = 0;
-    let mut most_common = input.to_vec();
-    while i < row_len && most_common.len() > 1 {
-        most_common = filter_common(true, &most_common, i);
-        i += 1;
+    #[test]
+    fn part1_example() {
+        assert_eq!(power_consumption(&parse_report(EXAMPLE).unwrap()).unwrap(), 198);
     }
-    let most = boolvec_to_int(most_common.first().unwrap().as_slice());
 
-    let mut j = 0;
-    let mut least_common = input.to_vec();
-    while j < row_len && least_common.len() > 1 {
-        least_common = filter_common(false, &least_common, j);
-        j += 1;
+    #[test]
+    fn part2_example() {
+        assert_eq!(life_support_rating(&parse_report(EXAMPLE).unwrap()).unwrap(), 230);
     }
-    let least = boolvec_to_int(least_common.first().unwrap().as_slice());
 
-    most * least
-}
\ No newline at end of file
+    #[test]
+    fn part1_input() {
+        assert_eq!(power_consumption(&parse_report(puzzle_input!(3)).unwrap()).unwrap(), 3912944);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(life_support_rating(&parse_report(puzzle_input!(3)).unwrap()).unwrap(), 4996233);
+    }
+
+    #[test]
+    fn rows_can_be_wider_than_32_bits() {
+        let high = format!("1{}", "0".repeat(63));
+        let high_and_low = format!("1{}1", "0".repeat(62));
+        let report = parse_report(&[high, high_and_low.clone(), high_and_low].join("\n")).unwrap();
+
+        let (gamma, epsilon) = ((1u128 << 63) + 1, (1u128 << 63) - 2);
+        assert_eq!(power_consumption(&report), Ok(gamma * epsilon));
+        assert_eq!(life_support_rating(&report), Ok(((1u128 << 63) + 1) * (1u128 << 63)));
+    }
+
+    #[test]
+    fn overflowing_power_consumption_is_an_error() {
+        let report = parse_report(&format!("{}{}", "1".repeat(64), "0".repeat(64))).unwrap();
+
+        assert_eq!(power_consumption(&report), Err("power consumption overflows".to_string()));
+        assert_eq!(parse_report(&"1".repeat(129)), Err("line 1: wider than 128 bits".to_string()));
+    }
+
+    #[test]
+    fn tie_breaks_pick_a_side() {
+        // the leftmost and rightmost columns are tied
+        let report = parse_report("110\n011").unwrap();
+
+        // gamma is 0b111, which leaves nothing for epsilon
+        assert_eq!(report.power_consumption(TieBreak::PreferOne), Ok(0));
+        assert_eq!(report.power_consumption(TieBreak::PreferZero), Ok(0b010 * 0b101));
+        assert_eq!(report.power_consumption(TieBreak::Error), Err("bit 0 is tied at 1 ones and 1 zeros".to_string()));
+
+        assert_eq!(report.life_support_rating(TieBreak::PreferOne), Ok(0b110 * 0b011));
+        assert_eq!(report.life_support_rating(TieBreak::PreferZero), Ok(0b011 * 0b110));
+        assert_eq!(report.life_support_rating(TieBreak::Error), Err("bit 2 is tied at 1 ones and 1 zeros".to_string()));
+
+        // tied columns are mostly zeros in part 1, but keep the ones in part 2
+        assert_eq!(power_consumption(&report), Ok(0b010 * 0b101));
+        assert_eq!(life_support_rating(&report), Ok(0b110 * 0b011));
+    }
+
+    #[test]
+    fn ragged_reports_are_errors() {
+        assert_eq!(parse_report("101\n10\n111"), Err("line 2 has 2 bits but line 1 has 3".to_string()));
+        assert_eq!(parse_report("101\n102"), Err("line 2: not a binary number: \"102\"".to_string()));
+    }
+}
@@ -1,12 +1,10 @@
+//! Day 4: Giant Squid.
+
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use aoc_runner_derive::{aoc, aoc_generator};
-
-fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> where T: Clone {
-    assert!(!v.is_empty());
-    (0..v[0].len())
-        .map(|i| v.iter().map(|inner| inner[i].clone()).collect::<Vec<T>>())
-        .collect()
-}
+use crate::registry::{self, Day};
+use crate::Solution;
 
 #[derive(Clone, Copy, Debug, PartialOrd, PartialEq)]
 enum Space {
@@ -14,60 +12,222 @@ enum Space {
     Marked(u64)
 }
 
-#[derive(Clone, Debug, PartialOrd, PartialEq)]
-struct BingoBoard {
-    spaces: Vec<Vec<Space>>
+impl Space {
+    fn unmarked(&self) -> Option<u64> {
+        match self {
+            Space::Unmarked(n) => Some(*n),
+            Space::Marked(_) => None
+        }
+    }
+}
+
+/// The line of marked spaces that won a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinPattern {
+    Row(usize),
+    Column(usize),
+    /// Top left to bottom right.
+    Diagonal,
+    /// Top right to bottom left.
+    AntiDiagonal,
+    FourCorners,
+    Blackout
+}
+
+/// Which patterns win on top of complete rows and columns, which always do
+/// unless the game is a blackout. Diagonals only count on square boards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BingoRules {
+    pub diagonals: bool,
+    pub four_corners: bool,
+    /// Only a fully marked board wins, and no other pattern counts.
+    pub blackout: bool
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Score {
+    pub pattern: WinPattern,
+    pub points: u64
+}
+
+/// A board that keeps an index from each number to its cells, plus running
+/// counts of marked cells per line, so calling a number and checking for a win
+/// don't need to scan the board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BingoBoard {
+    spaces: Vec<Vec<Space>>,
+    index: HashMap<u64, Vec<(usize, usize)>>,
+    row_marks: Vec<usize>,
+    col_marks: Vec<usize>,
+    diagonal_marks: usize,
+    anti_diagonal_marks: usize,
+    corner_marks: usize,
+    total_marks: usize,
+    unmarked_sum: u64,
+    full_row: Option<usize>,
+    full_col: Option<usize>
 }
 
 impl BingoBoard {
-    fn sum_unmarked(&self) -> u64 {
-        self.spaces.iter().map(|row| {
-            row.iter().map(|space| {
-                match space {
-                    Space::Unmarked(n) => *n,
-                    Space::Marked(_) => 0
+    fn new(spaces: Vec<Vec<Space>>) -> Self {
+        let mut index: HashMap<u64, Vec<(usize, usize)>> = HashMap::new();
+        let mut unmarked_sum = 0;
+
+        for (y, row) in spaces.iter().enumerate() {
+            for (x, space) in row.iter().enumerate() {
+                if let Space::Unmarked(n) = *space {
+                    index.entry(n).or_default().push((x, y));
+                    unmarked_sum += n;
                 }
-            }).sum::<u64>()
-        }).sum()
+            }
+        }
+
+        BingoBoard {
+            row_marks: vec![0; spaces.len()],
+            col_marks: vec![0; spaces[0].len()],
+            spaces,
+            index,
+            diagonal_marks: 0,
+            anti_diagonal_marks: 0,
+            corner_marks: 0,
+            total_marks: 0,
+            unmarked_sum,
+            full_row: None,
+            full_col: None
+        }
     }
 
-    fn has_won(&self) -> bool {
-        let any_row_win = self.spaces.iter().any(|row| {
-            row.iter().all(|space| match space {
-                Space::Marked(_) => true,
-                _ => false
-            })
-        });
+    pub fn height(&self) -> usize {
+        self.spaces.len()
+    }
 
-        let any_col_win = transpose(self.spaces.clone()).iter().any(|col| {
-            col.iter().all(|space| match space {
-                Space::Marked(_) => true,
-                _ => false
-            })
-        });
+    pub fn width(&self) -> usize {
+        self.spaces[0].len()
+    }
+
+    fn sum_unmarked(&self) -> u64 {
+        self.unmarked_sum
+    }
+
+    fn is_corner(&self, x: usize, y: usize) -> bool {
+        (x == 0 || x == self.width() - 1) && (y == 0 || y == self.height() - 1)
+    }
+
+    fn corner_count(&self) -> usize {
+        match (self.width(), self.height()) {
+            (1, 1) => 1,
+            (1, _) | (_, 1) => 2,
+            _ => 4
+        }
+    }
+
+    pub fn winning_pattern(&self, rules: &BingoRules) -> Option<WinPattern> {
+        let (height, width) = (self.height(), self.width());
+
+        if rules.blackout {
+            return Some(WinPattern::Blackout).filter(|_| self.total_marks == height * width);
+        }
+
+        if let Some(y) = self.full_row {
+            return Some(WinPattern::Row(y));
+        }
+
+        if let Some(x) = self.full_col {
+            return Some(WinPattern::Column(x));
+        }
+
+        if rules.diagonals && height == width {
+            if self.diagonal_marks == width {
+                return Some(WinPattern::Diagonal);
+            }
+
+            if self.anti_diagonal_marks == width {
+                return Some(WinPattern::AntiDiagonal);
+            }
+        }
+
+        if rules.four_corners && self.corner_marks == self.corner_count() {
+            return Some(WinPattern::FourCorners);
+        }
+
+        None
+    }
+
+    /// Every pattern that can win this board under `rules`, along with the
+    /// unmarked numbers that still have to be called to complete it.
+    pub fn patterns(&self, rules: &BingoRules) -> Vec<(WinPattern, Vec<u64>)> {
+        let (height, width) = (self.height(), self.width());
+        let mut patterns: Vec<(WinPattern, Vec<(usize, usize)>)> = vec![];
+
+        if rules.blackout {
+            patterns.push((WinPattern::Blackout, (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).collect()));
+        } else {
+            for y in 0..height {
+                patterns.push((WinPattern::Row(y), (0..width).map(|x| (x, y)).collect()));
+            }
+
+            for x in 0..width {
+                patterns.push((WinPattern::Column(x), (0..height).map(|y| (x, y)).collect()));
+            }
+
+            if rules.diagonals && height == width {
+                patterns.push((WinPattern::Diagonal, (0..width).map(|i| (i, i)).collect()));
+                patterns.push((WinPattern::AntiDiagonal, (0..width).map(|i| (width - 1 - i, i)).collect()));
+            }
+
+            if rules.four_corners {
+                patterns.push((WinPattern::FourCorners, vec![
+                    (0, 0), (width - 1, 0), (0, height - 1), (width - 1, height - 1)
+                ]));
+            }
+        }
+
+        patterns.into_iter().map(|(pattern, cells)| {
+            let mut needed = cells.iter()
+                .filter_map(|&(x, y)| self.spaces[y][x].unmarked())
+                .collect::<Vec<_>>();
+            needed.sort_unstable();
+            needed.dedup();
 
-        any_col_win || any_row_win
+            (pattern, needed)
+        }).collect()
     }
 
     fn call(&mut self, num: &u64) {
-        let new_spaces = self.spaces.iter().map(|row| {
-            row.iter()
-                .map(|space| {
-                    match space {
-                        Space::Unmarked(u) => {
-                            if u == num {
-                                Space::Marked(*u)
-                            } else {
-                                Space::Unmarked(*u)
-                            }
-                        }
-                        x => *x
-                    }
-                })
-                .collect()
-        }).collect();
-
-        self.spaces = new_spaces;
+        let (height, width) = (self.height(), self.width());
+
+        let cells = match self.index.remove(num) {
+            Some(cells) => cells,
+            None => return
+        };
+
+        for (x, y) in cells {
+            self.spaces[y][x] = Space::Marked(*num);
+            self.unmarked_sum -= num;
+            self.total_marks += 1;
+
+            self.row_marks[y] += 1;
+            if self.row_marks[y] == width && self.full_row.is_none() {
+                self.full_row = Some(y);
+            }
+
+            self.col_marks[x] += 1;
+            if self.col_marks[x] == height && self.full_col.is_none() {
+                self.full_col = Some(x);
+            }
+
+            if x == y {
+                self.diagonal_marks += 1;
+            }
+
+            if x + y == width - 1 {
+                self.anti_diagonal_marks += 1;
+            }
+
+            if self.is_corner(x, y) {
+                self.corner_marks += 1;
+            }
+        }
     }
 }
 
@@ -77,106 +237,373 @@ impl FromStr for BingoBoard {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let spaces = s
             .lines()
+            .filter(|l| !l.trim().is_empty())
             .map(|l| l
                 .split_whitespace()
-                .map(|n| {
-                    Space::Unmarked(n.parse().unwrap())
-                })
+                .map(|n| n.parse().map(Space::Unmarked).map_err(|_| "couldn't parse board number"))
                 .collect())
-            .collect::<Vec<Vec<Space>>>();
+            .collect::<Result<Vec<Vec<Space>>, _>>()?;
+
+        let width = spaces.first().ok_or("empty board")?.len();
+
+        if spaces.iter().any(|row| row.len() != width) {
+            return Err("board isn't rectangular");
+        }
 
-        Ok(BingoBoard { spaces })
+        // scores are sums of unmarked numbers, so they have to add up
+        spaces.iter().flatten()
+            .try_fold(0u64, |sum, space| match space {
+                Space::Unmarked(n) | Space::Marked(n) => sum.checked_add(*n)
+            })
+            .ok_or("board numbers are too large")?;
+
+        Ok(BingoBoard::new(spaces))
     }
 }
 
-#[derive(Clone, Debug, PartialOrd, PartialEq)]
-struct BingoGame {
+/// A board completing one of its winning patterns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Win {
+    pub board: usize,
+    pub number: u64,
+    /// 1-based position of `number` in the draw.
+    pub turn: usize,
+    pub score: Score
+}
+
+/// Every win in the order it happened, followed by the boards that never won.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timeline {
+    pub wins: Vec<Win>,
+    pub never_won: Vec<usize>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BingoGame {
     boards: Vec<BingoBoard>,
-    last_called: Option<u64>,
-    most_recent_winner: Option<BingoBoard>
+    rules: BingoRules
 }
 
 impl BingoGame {
-    fn winning_board(&self) -> Option<&BingoBoard> {
-        self.boards.iter().find(|board| board.has_won())
+    pub fn new(boards: Vec<BingoBoard>) -> Self {
+        BingoGame { boards, rules: BingoRules::default() }
     }
 
-    fn final_score(&self) -> Option<u64> {
-        match (self.winning_board(), self.last_called) {
-            (Some(b), Some(l)) => Some(b.sum_unmarked() * l),
-            _ => None
-        }
+    pub fn with_rules(self, rules: BingoRules) -> Self {
+        BingoGame { rules, ..self }
     }
 
-    fn last_winner_final_score(&self) -> Option<u64> {
-        match (&self.most_recent_winner, self.last_called) {
-            (Some(b), Some(l)) => Some(b.sum_unmarked() * l),
-            _ => None
-        }
+    pub fn boards(&self) -> &[BingoBoard] {
+        &self.boards
     }
 
-    fn call(&mut self, num: &u64) {
-        for board in self.boards.iter_mut() {
-            let had_won = board.has_won();
+    pub fn rules(&self) -> &BingoRules {
+        &self.rules
+    }
+
+    /// Marks `num` on every board, returning the boards that won because of it
+    /// along with their winning pattern.
+    pub fn call(&mut self, num: &u64) -> Vec<(usize, WinPattern)> {
+        let rules = self.rules;
+
+        self.boards.iter_mut().enumerate().filter_map(|(i, board)| {
+            let had_won = board.winning_pattern(&rules).is_some();
             board.call(num);
-            let has_won = board.has_won();
 
-            if !had_won && has_won {
-                self.most_recent_winner = Some(board.clone());
-                self.last_called = Some(*num);
+            if had_won {
+                None
+            } else {
+                board.winning_pattern(&rules).map(|pattern| (i, pattern))
             }
-        }
+        }).collect()
     }
 
-    fn play_game(&mut self, numbers: &[u64]) {
-        for number in numbers {
-            self.call(number);
+    /// Plays `numbers` out on a copy of the game, stopping once every board has won.
+    pub fn play_all(&self, numbers: &[u64]) -> Result<Timeline, String> {
+        let mut game = self.clone();
+        let mut timeline = Timeline::default();
+
+        for (turn, number) in numbers.iter().enumerate() {
+            for (board, pattern) in game.call(number) {
+                let points = game.boards[board].sum_unmarked().checked_mul(*number)
+                    .ok_or_else(|| format!("board {} wins on {}, but its score is too large", board, number))?;
+
+                timeline.wins.push(Win {
+                    board,
+                    number: *number,
+                    turn: turn + 1,
+                    score: Score { pattern, points }
+                });
+            }
 
-            if self.winning_board().is_some() {
+            if timeline.wins.len() == game.boards.len() {
                 break;
             }
         }
+
+        timeline.never_won = (0..game.boards.len())
+            .filter(|&i| timeline.wins.iter().all(|w| w.board != i))
+            .collect();
+
+        Ok(timeline)
     }
+}
 
-    fn play_until_end(&mut self, numbers: &[u64]) {
-        for number in numbers {
-            self.call(number);
-        }
+/// Finds a shortest draw that makes board `target` win strictly before every
+/// other board, where a board that wins on the same number counts as a tie and
+/// not a loss for it.
+///
+/// Drawing anything beyond the numbers of a single target pattern can only help
+/// the other boards, and the last number drawn is the one that completes the
+/// target, so another board ties or beats it exactly when one of its patterns
+/// needs a subset of those numbers. The answer is therefore the smallest target
+/// pattern that no other pattern fits inside, and an `Err` means none exists.
+pub fn shortest_winning_draw(game: &BingoGame, target: usize) -> Result<Vec<u64>, &'static str> {
+    let rules = game.rules();
+    let board = game.boards().get(target).ok_or("no such board")?;
+
+    if board.winning_pattern(rules).is_some() {
+        return Err("target board has already won");
     }
+
+    let rivals = game.boards().iter().enumerate()
+        .filter(|&(i, _)| i != target)
+        .flat_map(|(_, b)| b.patterns(rules))
+        .map(|(_, needed)| needed)
+        .collect::<Vec<_>>();
+
+    board.patterns(rules).into_iter()
+        .map(|(_, needed)| needed)
+        .filter(|needed| {
+            let drawn = needed.iter().collect::<HashSet<_>>();
+            !rivals.iter().any(|rival| rival.iter().all(|n| drawn.contains(n)))
+        })
+        .min_by_key(|needed| needed.len())
+        .ok_or("every winning pattern on the target board also completes another board")
 }
 
 #[aoc_generator(day4)]
-fn parse_inputs(input: &str) -> (Vec<u64>, BingoGame) {
+fn parse_inputs(input: &str) -> Result<(Vec<u64>, BingoGame), String> {
     let sections = input.split("\n\n").collect::<Vec<&str>>();
 
     let nums = sections.first().cloned().unwrap()
+        .trim()
         .split(",")
-        .map(|n| n.parse().unwrap())
-        .collect::<Vec<u64>>();
+        .map(|n| n.parse().map_err(|_| format!("couldn't parse called number {:?}", n)))
+        .collect::<Result<Vec<u64>, _>>()?;
 
     let boards = sections[1..]
         .iter()
-        .map(|b| b.parse().unwrap())
-        .collect::<Vec<BingoBoard>>();
+        .filter(|b| !b.trim().is_empty())
+        .enumerate()
+        .map(|(i, b)| b.parse().map_err(|e| format!("board {}: {}", i, e)))
+        .collect::<Result<Vec<BingoBoard>, _>>()?;
 
-    (nums, BingoGame { boards, last_called: None, most_recent_winner: None })
+    Ok((nums, BingoGame::new(boards)))
 }
 
-
 #[aoc(day4, part1)]
-fn final_score(input: &(Vec<u64>, BingoGame)) -> u64 {
-    let (numbers, mut game) = input.clone();
-
-    game.play_game(&numbers);
+fn final_score(input: &(Vec<u64>, BingoGame)) -> Result<u64, String> {
+    let (numbers, game) = input;
 
-    game.final_score().unwrap()
+    game.play_all(numbers)?.wins.first().map(|win| win.score.points).ok_or_else(|| "no board wins".to_string())
 }
 
 #[aoc(day4, part2)]
-fn last_winner_final_score(input: &(Vec<u64>, BingoGame)) -> u64 {
-    let (numbers, mut game) = input.clone();
+fn last_winner_final_score(input: &(Vec<u64>, BingoGame)) -> Result<u64, String> {
+    let (numbers, game) = input;
+
+    game.play_all(numbers)?.wins.last().map(|win| win.score.points).ok_or_else(|| "no board wins".to_string())
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = (Vec<u64>, BingoGame);
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_inputs(input)
+    }
 
-    game.play_until_end(&numbers);
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        final_score(input)
+    }
 
-    game.last_winner_final_score().unwrap()
-}
\ No newline at end of file
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        last_winner_final_score(input)
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 4,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(final_score(&parse_inputs(EXAMPLE).unwrap()), Ok(4512));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(last_winner_final_score(&parse_inputs(EXAMPLE).unwrap()), Ok(1924));
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(final_score(&parse_inputs(puzzle_input!(4)).unwrap()), Ok(11774));
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(last_winner_final_score(&parse_inputs(puzzle_input!(4)).unwrap()), Ok(4495));
+    }
+
+    #[test]
+    fn malformed_boards_are_errors() {
+        assert!("".parse::<BingoBoard>().is_err());
+        assert!("1 2\n3".parse::<BingoBoard>().is_err());
+        assert!("1 x\n3 4".parse::<BingoBoard>().is_err());
+        assert!("18446744073709551615 1".parse::<BingoBoard>().is_err());
+    }
+
+    const SQUARE: &str = "1 2 3\n4 5 6\n7 8 9";
+
+    fn first_win(board: &str, rules: BingoRules, numbers: &[u64]) -> Option<Win> {
+        let game = BingoGame::new(vec![board.parse().unwrap()]).with_rules(rules);
+        game.play_all(numbers).unwrap().wins.first().copied()
+    }
+
+    #[test]
+    fn diagonals_win_on_square_boards() {
+        let rules = BingoRules { diagonals: true, ..BingoRules::default() };
+
+        assert_eq!(first_win(SQUARE, BingoRules::default(), &[1, 5, 9]), None);
+        assert_eq!(first_win(SQUARE, rules, &[1, 5, 9]).unwrap().score, Score { pattern: WinPattern::Diagonal, points: 30 * 9 });
+        assert_eq!(first_win(SQUARE, rules, &[3, 5, 7]).unwrap().score, Score { pattern: WinPattern::AntiDiagonal, points: 30 * 7 });
+
+        // there's no diagonal to complete on a board that isn't square
+        assert_eq!(first_win("1 2 3\n4 5 6", rules, &[1, 5]), None);
+    }
+
+    #[test]
+    fn four_corners_win() {
+        let rules = BingoRules { four_corners: true, ..BingoRules::default() };
+
+        assert_eq!(first_win(SQUARE, BingoRules::default(), &[1, 3, 7, 9]), None);
+        assert_eq!(first_win(SQUARE, rules, &[1, 3, 7, 9]).unwrap().score, Score { pattern: WinPattern::FourCorners, points: 25 * 9 });
+    }
+
+    #[test]
+    fn blackout_needs_every_number() {
+        let rules = BingoRules { blackout: true, ..BingoRules::default() };
+
+        assert_eq!(first_win(SQUARE, BingoRules::default(), &[4, 5, 6]).unwrap().score, Score { pattern: WinPattern::Row(1), points: 30 * 6 });
+
+        let win = first_win(SQUARE, rules, &[4, 5, 6, 1, 2, 3, 7, 8, 9]).unwrap();
+        assert_eq!((win.turn, win.score), (9, Score { pattern: WinPattern::Blackout, points: 0 }));
+        assert_eq!(first_win(SQUARE, rules, &[4, 5, 6, 1, 2, 3, 7, 8]), None);
+    }
+
+    #[test]
+    fn boards_must_be_rectangular() {
+        assert_eq!("1 2\n3".parse::<BingoBoard>(), Err("board isn't rectangular"));
+        assert_eq!(
+            parse_inputs("1,2\n\n1 2\n3 4\n\n1 2 3\n4 5").unwrap_err(),
+            "board 1: board isn't rectangular"
+        );
+    }
+
+    #[test]
+    fn boards_that_never_win_are_listed() {
+        let game = BingoGame::new(vec![
+            "1 2\n3 4".parse().unwrap(),
+            "5 6\n7 8".parse().unwrap(),
+            "1 3\n9 9".parse().unwrap()
+        ]);
+
+        let timeline = game.play_all(&[1, 2, 3]).unwrap();
+
+        assert_eq!(timeline.wins, vec![
+            Win { board: 0, number: 2, turn: 2, score: Score { pattern: WinPattern::Row(0), points: 7 * 2 } },
+            Win { board: 2, number: 3, turn: 3, score: Score { pattern: WinPattern::Row(0), points: 18 * 3 } }
+        ]);
+        assert_eq!(timeline.never_won, vec![1]);
+    }
+
+    #[test]
+    fn overflowing_scores_are_errors() {
+        let input = "3,1099511627776\n\n1099511627776 3\n1099511627777 1099511627778";
+
+        assert_eq!(
+            final_score(&parse_inputs(input).unwrap()),
+            Err("board 0 wins on 1099511627776, but its score is too large".to_string())
+        );
+    }
+
+    fn game(boards: &[&str]) -> BingoGame {
+        BingoGame::new(boards.iter().map(|b| b.parse().unwrap()).collect())
+    }
+
+    #[test]
+    fn finds_shortest_winning_draw() {
+        // the rival shares the target's top row, so that row would only tie
+        let game = game(&["1 2\n3 4", "1 2\n5 6"]);
+        let draw = shortest_winning_draw(&game, 0).unwrap();
+
+        assert_eq!(draw, vec![3, 4]);
+
+        let timeline = game.play_all(&draw).unwrap();
+        assert_eq!(timeline.wins.iter().map(|w| w.board).collect::<Vec<_>>(), vec![0]);
+
+        assert_eq!(shortest_winning_draw(&game, 2), Err("no such board"));
+    }
+
+    #[test]
+    fn no_winning_draw_when_rivals_fit_inside_every_pattern() {
+        // every row and column of the target contains a 1 or a 4, and each of
+        // those wins a rival board on its own
+        let game = game(&["1 2\n3 4", "1", "4"]);
+
+        assert_eq!(
+            shortest_winning_draw(&game, 0),
+            Err("every winning pattern on the target board also completes another board")
+        );
+        assert_eq!(shortest_winning_draw(&game, 1), Ok(vec![1]));
+
+        let mut played = game.clone();
+        played.call(&1);
+        assert_eq!(shortest_winning_draw(&played, 1), Err("target board has already won"));
+    }
+}
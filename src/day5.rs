@@ -1,6 +1,10 @@
+//! Day 5: Hydrothermal Venture.
+
 use std::cmp::Ordering;
 use std::str::FromStr;
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day};
+use crate::Solution;
 
 #[derive(Debug)]
 struct Step {
@@ -8,8 +12,9 @@ struct Step {
     run: i64
 }
 
+/// A straight or 45° line of vents, walked from `start` one step at a time.
 #[derive(Debug)]
-struct Segment {
+pub struct Segment {
     start: (i64, i64),
     length: u64,
     step: Step
@@ -23,53 +28,63 @@ fn cmp<T: Ord>(x: T, y: T) -> i64 {
     }
 }
 
+/// How far the ocean floor reaches along each axis. Every vent is on it.
+pub const FLOOR_SIZE: usize = 1000;
+
+fn parse_point(s: &str) -> Result<(i64, i64), &'static str> {
+    let (x, y) = s.split_once(",").ok_or("expected a point like x,y")?;
+
+    let coord = |n: &str| match n.trim().parse::<usize>() {
+        Ok(c) if c < FLOOR_SIZE => Ok(c as i64),
+        Ok(_) => Err("coordinate is off the ocean floor"),
+        Err(_) => Err("couldn't parse coordinate")
+    };
+
+    Ok((coord(x)?, coord(y)?))
+}
+
 impl FromStr for Segment {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s
-            .split(" -> ")
-            .map(|part| {
-                part
-                    .split(",")
-                    .map(|n| n.parse().unwrap())
-                    .collect::<Vec<i64>>()
-            })
-            .collect::<Vec<Vec<i64>>>();
+        let (start, end) = s.split_once(" -> ").ok_or("expected two points separated by ' -> '")?;
+        let (start_x, start_y) = parse_point(start)?;
+        let (end_x, end_y) = parse_point(end)?;
+
+        let x_diff = (end_x - start_x).abs();
+        let y_diff = (end_y - start_y).abs();
 
-        if let (&[start_x, start_y], &[end_x, end_y]) =
-                (&parts.first().unwrap()[..], &parts.last().unwrap()[..]) {
-            let x_diff = (end_x - start_x).abs();
-            let y_diff = (end_y - start_y).abs();
+        if x_diff != 0 && y_diff != 0 && x_diff != y_diff {
+            return Err("segment isn't horizontal, vertical or diagonal");
+        }
 
-            let length = std::cmp::max(x_diff, y_diff) + 1;
+        let length = std::cmp::max(x_diff, y_diff) + 1;
 
-            let step = Step {
-                rise: cmp(end_y, start_y),
-                run: cmp(end_x, start_x)
-            };
+        let step = Step {
+            rise: cmp(end_y, start_y),
+            run: cmp(end_x, start_x)
+        };
 
-            Ok(Segment {
-                start: (start_x, start_y),
-                length: length as u64,
-                step
-            })
-        } else {
-            Err("can't match")
-        }
+        Ok(Segment {
+            start: (start_x, start_y),
+            length: length as u64,
+            step
+        })
     }
 }
 
 #[aoc_generator(day5)]
-fn parse_inputs(input: &str) -> Vec<Segment> {
-    input.lines().map(|line| line.parse().unwrap()).collect()
+fn parse_inputs(input: &str) -> Result<Vec<Segment>, String> {
+    input.lines().enumerate()
+        .map(|(i, line)| line.parse().map_err(|e| format!("line {}: {}: {:?}", i + 1, e, line)))
+        .collect()
 }
 
 struct Grid(Vec<Vec<u64>>);
 
 impl Grid {
     fn new() -> Self {
-        Grid(vec![vec![0; 1000]; 1000])
+        Grid(vec![vec![0; FLOOR_SIZE]; FLOOR_SIZE])
     }
 
     fn paint(&mut self, segment: &Segment) {
@@ -123,3 +138,81 @@ fn count_overlaps_2(segments: &[Segment]) -> u64 {
     grid.paint_all(segments);
     grid.count_overlapping()
 }
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = Vec<Segment>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_inputs(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        Ok(count_overlaps(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        Ok(count_overlaps_2(input))
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 5,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(count_overlaps(&parse_inputs(EXAMPLE).unwrap()), 5);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(count_overlaps_2(&parse_inputs(EXAMPLE).unwrap()), 12);
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(count_overlaps(&parse_inputs(puzzle_input!(5)).unwrap()), 7438);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(count_overlaps_2(&parse_inputs(puzzle_input!(5)).unwrap()), 21406);
+    }
+
+    #[test]
+    fn malformed_segments_are_errors() {
+        assert!("1,2 -> 3".parse::<Segment>().is_err());
+        assert!("1,2 -> 3,x".parse::<Segment>().is_err());
+        assert!("1,2 -> 3,4 -> 5,6".parse::<Segment>().is_err());
+        assert!("-1,2 -> 3,2".parse::<Segment>().is_err());
+        assert!("0,0 -> 1,2".parse::<Segment>().is_err());
+        assert_eq!("999,0 -> 0,999".parse::<Segment>().map(|s| s.length), Ok(1000));
+        assert_eq!("1000,0 -> 0,0".parse::<Segment>().unwrap_err(), "coordinate is off the ocean floor");
+        assert_eq!(parse_inputs("0,0 -> 2000,0").err(), Some("line 1: coordinate is off the ocean floor: \"0,0 -> 2000,0\"".to_string()));
+    }
+}
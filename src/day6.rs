@@ -1,15 +1,19 @@
+//! Day 6: Lanternfish.
+
 use std::collections::HashMap;
 use std::str::FromStr;
 use aoc_runner_derive::{aoc};
+use crate::registry::{self, Day, Run, Variant};
+use crate::Solution;
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-struct Lanternfish(u32);
+pub struct Lanternfish(u32);
 
 impl FromStr for Lanternfish {
     type Err = &'static str;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        input.parse::<u32>().map(|i| Lanternfish(i)).map_err(|_| "couldn't parse timer")
+        input.parse::<u32>().map(Lanternfish).map_err(|_| "couldn't parse timer")
     }
 }
 
@@ -40,10 +44,10 @@ impl Lanternfish {
 }
 
 #[derive(Debug, Clone)]
-struct School(Vec<Lanternfish>);
+pub struct School(Vec<Lanternfish>);
 
 impl School {
-    fn step(&mut self) {
+    pub fn step(&mut self) {
         let (existing, maybe_new): (Vec<Lanternfish>, Vec<Option<Lanternfish>>) = self.0.iter()
             .map(|f| f.step())
             .unzip();
@@ -53,7 +57,7 @@ impl School {
         self.0 = existing.iter().chain(new.iter()).cloned().collect();
     }
 
-    fn count(&self) -> usize {
+    pub fn count(&self) -> usize {
         self.0.len()
     }
 }
@@ -84,10 +88,11 @@ fn lanternfish_count_80(input: &str) -> usize {
     school.count()
 }
 
-struct InPlaceSchool(Vec<Lanternfish>);
+#[derive(Debug, Clone)]
+pub struct InPlaceSchool(Vec<Lanternfish>);
 
 impl InPlaceSchool {
-    fn step_mut(&mut self) {
+    pub fn step_mut(&mut self) {
         let mut new: Vec<Lanternfish> = vec![];
 
         for lf in self.0.iter_mut() {
@@ -99,7 +104,7 @@ impl InPlaceSchool {
         self.0.append(&mut new);
     }
 
-    fn count(&self) -> usize {
+    pub fn count(&self) -> usize {
         self.0.len()
     }
 }
@@ -131,10 +136,13 @@ fn lanternfish_count_80_2(input: &str) -> usize {
     school.count()
 }
 
-struct CountingSchool(HashMap<Lanternfish, usize>);
+/// Counts fish by timer rather than keeping each one, so a step costs the same
+/// however big the school gets.
+#[derive(Debug, Clone)]
+pub struct CountingSchool(HashMap<Lanternfish, usize>);
 
 impl CountingSchool {
-    fn step_mut(&mut self) {
+    pub fn step_mut(&mut self) {
         let mut new: HashMap<Lanternfish, usize> = HashMap::new();
 
         for (lf, count) in self.0.iter() {
@@ -152,9 +160,19 @@ impl CountingSchool {
         self.0 = new;
     }
 
-    fn count(&self) -> usize {
+    pub fn count(&self) -> usize {
         self.0.values().sum::<usize>()
     }
+
+    pub fn count_after(&self, days: usize) -> usize {
+        let mut school = self.clone();
+
+        for _ in 0..days {
+            school.step_mut();
+        }
+
+        school.count()
+    }
 }
 
 impl FromStr for CountingSchool {
@@ -180,13 +198,121 @@ impl FromStr for CountingSchool {
 }
 
 
+fn part1_immutable(input: &str) -> Result<Run, String> {
+    registry::solve_with(input, |s| s.parse::<School>().map_err(String::from), |school| {
+        let mut school = school.clone();
+        (0..80).for_each(|_| school.step());
+        Ok(school.count())
+    })
+}
+
+fn part1_mutable(input: &str) -> Result<Run, String> {
+    registry::solve_with(input, |s| s.parse::<InPlaceSchool>().map_err(String::from), |school| {
+        let mut school = school.clone();
+        (0..80).for_each(|_| school.step_mut());
+        Ok(school.count())
+    })
+}
+
 #[aoc(day6, part2)]
 fn lanternfish_count_256(input: &str) -> usize {
-    let mut school = input.parse::<CountingSchool>().unwrap();
+    input.parse::<CountingSchool>().unwrap().count_after(256)
+}
 
-    for _ in 0..256 {
-        school.step_mut();
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = CountingSchool;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        input.parse().map_err(|e: &str| e.to_string())
     }
 
-    school.count()
-}
\ No newline at end of file
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        Ok(input.count_after(80))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        Ok(input.count_after(256))
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 6,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[
+        Variant { part: 1, name: "immutable", solver: part1_immutable },
+        Variant { part: 1, name: "mutable", solver: part1_mutable }
+    ],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "3,4,3,1,2";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(lanternfish_count_80(EXAMPLE), 5934);
+        assert_eq!(lanternfish_count_80_2(EXAMPLE), 5934);
+        assert_eq!(EXAMPLE.parse::<CountingSchool>().unwrap().count_after(80), 5934);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(lanternfish_count_256(EXAMPLE), 26984457539);
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(lanternfish_count_80(puzzle_input!(6)), 345387);
+        assert_eq!(lanternfish_count_80_2(puzzle_input!(6)), 345387);
+        assert_eq!(puzzle_input!(6).parse::<CountingSchool>().unwrap().count_after(80), 345387);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(lanternfish_count_256(puzzle_input!(6)), 1574445493136);
+    }
+
+    #[test]
+    fn implementations_agree_day_by_day() {
+        let mut immutable = EXAMPLE.parse::<School>().unwrap();
+        let mut mutable = EXAMPLE.parse::<InPlaceSchool>().unwrap();
+        let mut counting = EXAMPLE.parse::<CountingSchool>().unwrap();
+
+        for _ in 0..80 {
+            immutable.step();
+            mutable.step_mut();
+            counting.step_mut();
+
+            assert_eq!(immutable.count(), mutable.count());
+            assert_eq!(immutable.count(), counting.count());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn schools_agree_on_random_timers(timers in prop::collection::vec(0..=8u32, 1..20), days in 0..60usize) {
+            let input = timers.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(",");
+
+            let mut immutable = input.parse::<School>().unwrap();
+            let mut mutable = input.parse::<InPlaceSchool>().unwrap();
+            let counting = input.parse::<CountingSchool>().unwrap();
+
+            for _ in 0..days {
+                immutable.step();
+                mutable.step_mut();
+            }
+
+            prop_assert_eq!(immutable.count(), mutable.count());
+            prop_assert_eq!(immutable.count(), counting.count_after(days));
+        }
+    }
+}
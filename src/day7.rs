@@ -1,52 +1,189 @@
+//! Day 7: The Treachery of Whales.
+
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day, Run, Variant};
+use crate::Solution;
+
+/// The most crab-to-position costs the exhaustive solutions will add up before
+/// giving up. The real input needs a couple of million.
+pub const MAX_EXHAUSTIVE_STEPS: u64 = 1 << 30;
+
+const OVERFLOW: &str = "fuel cost overflows";
 
 #[aoc_generator(day7)]
-fn parse_crabs(input: &str) -> Vec<i64> {
-    input.split(",").map(|t| t.parse().unwrap()).collect()
+fn parse_crabs(input: &str) -> Result<Vec<i64>, String> {
+    input.trim().split(',').enumerate()
+        .map(|(i, t)| t.trim().parse().map_err(|_| format!("crab {}: not a position: {:?}", i + 1, t)))
+        .collect()
 }
 
-fn total_cost_to_align<F>(input: &[i64], position: i64, cost_func: F) -> i64 where F: Fn(i64, i64) -> i64 {
-    input.iter().map(|c| cost_func(*c, position)).sum()
+/// The fuel it takes to move every crab to `position`, or `None` if it's too
+/// much to count.
+fn total_cost_to_align<F>(input: &[i64], position: i64, cost_func: F) -> Option<i64> where F: Fn(i64) -> Option<i64> {
+    input.iter().try_fold(0i64, |total, c| total.checked_add(cost_func(c.checked_sub(position)?.checked_abs()?)?))
 }
 
-#[aoc(day7, part1)]
-fn cost_to_cheapest_alignment(input: &[i64]) -> usize {
-    let min_position = *input.iter().min().unwrap();
-    let max_position = *input.iter().max().unwrap();
+fn linear(distance: i64) -> Option<i64> {
+    Some(distance)
+}
 
-    let mut cheapest_alignment = (max_position - min_position) * (input.len() as i64);
+fn triangular(distance: i64) -> Option<i64> {
+    Some(distance.checked_mul(distance.checked_add(1)?)? / 2)
+}
 
-    for pos in min_position..=max_position {
-        let total_cost = total_cost_to_align(input, pos, |c, pos| (c - pos).abs());
+/// Tries every position between the outermost crabs, as long as that's not
+/// too many sums to do.
+fn cheapest_of_every_position<F>(input: &[i64], cost_func: F) -> Result<usize, String> where F: Fn(i64) -> Option<i64> {
+    let min_position = *input.iter().min().ok_or("no crabs")?;
+    let max_position = *input.iter().max().ok_or("no crabs")?;
 
-        if total_cost < cheapest_alignment {
-            cheapest_alignment = total_cost;
-        }
+    match max_position.abs_diff(min_position).checked_add(1).and_then(|p| p.checked_mul(input.len() as u64)) {
+        Some(steps) if steps <= MAX_EXHAUSTIVE_STEPS => {},
+        _ => return Err("crabs are too far apart to try every position".to_string())
     }
 
-    cheapest_alignment.try_into().unwrap()
+    let cheapest = (min_position..=max_position)
+        .filter_map(|pos| total_cost_to_align(input, pos, &cost_func))
+        .min()
+        .ok_or(OVERFLOW)?;
+
+    Ok(cheapest.try_into().unwrap())
+}
+
+#[aoc(day7, part1, exhaustive)]
+fn cost_to_cheapest_alignment(input: &[i64]) -> Result<usize, String> {
+    cheapest_of_every_position(input, linear)
 }
 
+#[aoc(day7, part2, exhaustive)]
+fn cost_to_cheapest_alignment_2(input: &[i64]) -> Result<usize, String> {
+    cheapest_of_every_position(input, triangular)
+}
+
+/// The sum of distances is smallest at the median, so there's no need to try
+/// every position.
+#[aoc(day7, part1)]
+fn cost_to_median(input: &[i64]) -> Result<usize, String> {
+    let mut crabs = input.to_vec();
+    crabs.sort_unstable();
+
+    let median = *crabs.get(crabs.len() / 2).ok_or("no crabs")?;
+    let cost = total_cost_to_align(&crabs, median, linear).ok_or(OVERFLOW)?;
+
+    Ok(cost.try_into().unwrap())
+}
+
+/// With triangular costs the best position is within half a step of the mean,
+/// so only the positions either side of it need trying.
 #[aoc(day7, part2)]
-fn cost_to_cheapest_alignment_2(input: &[i64]) -> usize {
-    let min_position = *input.iter().min().unwrap();
-    let max_position = *input.iter().max().unwrap();
+fn cost_near_mean(input: &[i64]) -> Result<usize, String> {
+    if input.is_empty() {
+        return Err("no crabs".to_string());
+    }
+
+    let sum = input.iter().try_fold(0i64, |sum, c| sum.checked_add(*c)).ok_or(OVERFLOW)?;
+    let mean = sum.div_euclid(input.len() as i64);
 
-    let cost_func = |c: i64, pos: i64| {
-        let diff = (c - pos).abs();
+    let cost = (mean.saturating_sub(1)..=mean.saturating_add(1))
+        .filter_map(|pos| total_cost_to_align(input, pos, triangular))
+        .min()
+        .ok_or(OVERFLOW)?;
 
-        diff * (diff + 1) / 2
-    };
+    Ok(cost.try_into().unwrap())
+}
+
+fn part1_exhaustive(input: &str) -> Result<Run, String> {
+    registry::solve_with(input, parse_crabs, |crabs| cost_to_cheapest_alignment(crabs))
+}
 
-    let mut cheapest_alignment = cost_func(max_position, min_position) * (input.len() as i64);
+fn part2_exhaustive(input: &str) -> Result<Run, String> {
+    registry::solve_with(input, parse_crabs, |crabs| cost_to_cheapest_alignment_2(crabs))
+}
 
-    for pos in min_position..=max_position {
-        let total_cost = total_cost_to_align(input, pos, cost_func);
+pub struct Puzzle;
 
-        if total_cost < cheapest_alignment {
-            cheapest_alignment = total_cost;
-        }
+impl Solution for Puzzle {
+    type Input = Vec<i64>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_crabs(input)
     }
 
-    cheapest_alignment.try_into().unwrap()
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        cost_to_median(input)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        cost_near_mean(input)
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 7,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[
+        Variant { part: 1, name: "exhaustive", solver: part1_exhaustive },
+        Variant { part: 2, name: "exhaustive", solver: part2_exhaustive }
+    ],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "16,1,2,0,4,2,7,1,2,14";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(cost_to_median(&parse_crabs(EXAMPLE).unwrap()), Ok(37));
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(cost_near_mean(&parse_crabs(EXAMPLE).unwrap()), Ok(168));
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(cost_to_median(&parse_crabs(puzzle_input!(7)).unwrap()), Ok(354129));
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(cost_near_mean(&parse_crabs(puzzle_input!(7)).unwrap()), Ok(98905973));
+    }
+
+    #[test]
+    fn malformed_crabs_are_errors() {
+        assert_eq!(parse_crabs("1,x"), Err("crab 2: not a position: \"x\"".to_string()));
+        assert_eq!(parse_crabs("1,,2"), Err("crab 2: not a position: \"\"".to_string()));
+        assert_eq!(parse_crabs(""), Err("crab 1: not a position: \"\"".to_string()));
+    }
+
+    #[test]
+    fn far_apart_crabs_are_solved() {
+        assert_eq!(parse_crabs("1,-1"), Ok(vec![1, -1]));
+        assert_eq!(cost_to_median(&[0, 1_000_000_000, 3_000_000_000]), Ok(3_000_000_000));
+        assert_eq!(cost_near_mean(&[0, 2_000_000]), Ok(1_000_001_000_000));
+        assert_eq!(cost_to_cheapest_alignment(&[0, 3_000_000_000]), Err("crabs are too far apart to try every position".to_string()));
+        assert_eq!(cost_near_mean(&[i64::MIN, i64::MAX]), Err(OVERFLOW.to_string()));
+        assert_eq!(cost_to_median(&[i64::MIN, i64::MAX]), Err(OVERFLOW.to_string()));
+    }
+
+    proptest! {
+        #[test]
+        fn median_matches_brute_force(crabs in prop::collection::vec(0..200i64, 1..50)) {
+            prop_assert_eq!(cost_to_median(&crabs), cost_to_cheapest_alignment(&crabs));
+        }
+
+        #[test]
+        fn mean_matches_brute_force(crabs in prop::collection::vec(0..200i64, 1..50)) {
+            prop_assert_eq!(cost_near_mean(&crabs), cost_to_cheapest_alignment_2(&crabs));
+        }
+    }
 }
@@ -1,29 +1,62 @@
+//! Day 8: Seven Segment Search.
+
 use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day};
+use crate::Solution;
 
-struct Signal {
+/// One display's ten unique patterns and four output digits, each with its
+/// wires sorted.
+pub struct Signal {
     patterns: Vec<String>,
     output: Vec<String>
 }
 
+/// Sorts a pattern's wires, so the same digit always reads the same.
+fn parse_pattern(s: &str) -> Result<String, &'static str> {
+    let mut wires = s.chars().collect::<Vec<_>>();
+
+    if wires.iter().any(|w| !('a'..='g').contains(w)) {
+        return Err("wires must be a to g");
+    }
+
+    wires.sort();
+    wires.dedup();
+
+    if wires.len() != s.len() {
+        return Err("pattern repeats a wire");
+    }
+
+    Ok(wires.into_iter().collect())
+}
+
+impl FromStr for Signal {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pats, outs) = s.split_once(" | ").ok_or("expected patterns and output separated by ' | '")?;
+
+        let patterns = pats.split_whitespace().map(parse_pattern).collect::<Result<Vec<_>, _>>()?;
+        let output = outs.split_whitespace().map(parse_pattern).collect::<Result<Vec<_>, _>>()?;
+
+        if patterns.len() != 10 {
+            return Err("expected 10 patterns");
+        }
+
+        if output.len() != 4 {
+            return Err("expected 4 output digits");
+        }
+
+        Ok(Signal { patterns, output })
+    }
+}
+
 #[aoc_generator(day8)]
-fn parse_input(input: &str) -> Vec<Signal> {
-    input.lines().filter_map(|line| {
-        line.split_once(" | ").map(|(pats, outs)| {
-            Signal {
-                patterns: pats.split_whitespace().map(|s| {
-                    let mut r = s.chars().collect::<Vec<_>>();
-                    r.sort();
-                    r.into_iter().collect()
-                }).collect(),
-                output: outs.split_whitespace().map(|s| {
-                    let mut r = s.chars().collect::<Vec<_>>();
-                    r.sort();
-                    r.into_iter().collect()
-                }).collect()
-            }
-        })
-    }).collect::<Vec<_>>()
+fn parse_input(input: &str) -> Result<Vec<Signal>, String> {
+    input.lines().enumerate()
+        .map(|(i, line)| line.parse().map_err(|e| format!("line {}: {}: {:?}", i + 1, e, line)))
+        .collect()
 }
 
 #[aoc(day8, part1)]
@@ -38,92 +71,178 @@ fn count_digits_1478(signals: &[Signal]) -> usize {
     })
 }
 
-fn create_mapping(signal: &Signal) -> HashMap<String, usize> {
+/// Works out which pattern is which digit, or `None` if the patterns aren't the
+/// ten digits with their wires crossed.
+fn create_mapping(signal: &Signal) -> Option<HashMap<String, usize>> {
     let mut all_patterns: HashSet<String> = HashSet::from_iter(signal.patterns.iter().cloned());
     let mut pattern_map = HashMap::new();
 
-    let one_pattern = all_patterns.iter().find(|p| p.len() == 2).unwrap().clone();
+    let one_pattern = all_patterns.iter().find(|p| p.len() == 2)?.clone();
     let right_wires: Vec<_> = one_pattern.chars().collect();
     all_patterns.remove(&one_pattern);
     pattern_map.insert(one_pattern, 1);
 
-    let seven_pattern = all_patterns.iter().find(|p| p.len() == 3).unwrap().clone();
+    let seven_pattern = all_patterns.iter().find(|p| p.len() == 3)?.clone();
     all_patterns.remove(&seven_pattern);
     pattern_map.insert(seven_pattern, 7);
 
-    let four_pattern = all_patterns.iter().find(|p| p.len() == 4).unwrap().clone();
+    let four_pattern = all_patterns.iter().find(|p| p.len() == 4)?.clone();
     all_patterns.remove(&four_pattern);
     pattern_map.insert(four_pattern, 4);
 
-    let eight_pattern = all_patterns.iter().find(|p| p.len() == 7).unwrap().clone();
+    let eight_pattern = all_patterns.iter().find(|p| p.len() == 7)?.clone();
     all_patterns.remove(&eight_pattern);
     pattern_map.insert(eight_pattern, 8);
 
     let three_pattern = all_patterns.iter().find(|p| {
         p.len() == 5 && right_wires.iter().all(|wire| p.contains(*wire))
-    }).unwrap().clone();
+    })?.clone();
     all_patterns.remove(&three_pattern);
 
     let six_pattern = all_patterns.iter().find(|p| {
         p.len() == 6 && right_wires.iter().filter(|&&wire| p.contains(wire)).count() == 1
-    }).unwrap().clone();
-    let right_bottom_wire = six_pattern.chars().find(|wire| right_wires.contains(wire)).unwrap();
+    })?.clone();
+    let right_bottom_wire = six_pattern.chars().find(|wire| right_wires.contains(wire))?;
     all_patterns.remove(&six_pattern);
     pattern_map.insert(six_pattern, 6);
 
     let right_top_wire = *right_wires.iter()
-        .filter(|&&w| w != right_bottom_wire)
-        .next()
-        .unwrap();
+        .find(|&&w| w != right_bottom_wire)?;
 
     let two_pattern = all_patterns.iter().find(|p| {
         p.len() == 5 && p.contains(right_top_wire)
-    }).unwrap().clone();
+    })?.clone();
     let left_bottom_wire = two_pattern.chars()
-        .filter(|&w| {
+        .find(|&w| {
             !three_pattern.contains(w)
-        })
-        .next()
-        .unwrap();
+        })?;
     all_patterns.remove(&two_pattern);
     pattern_map.insert(two_pattern, 2);
     pattern_map.insert(three_pattern, 3);
 
     let five_pattern = all_patterns.iter().find(|p| {
         p.len() == 5 && p.contains(right_bottom_wire)
-    }).unwrap().clone();
+    })?.clone();
     all_patterns.remove(&five_pattern);
     pattern_map.insert(five_pattern, 5);
 
     let zero_pattern = all_patterns.iter().find(|p| {
         p.len() == 6 && p.contains(left_bottom_wire)
-    }).unwrap().clone();
+    })?.clone();
     all_patterns.remove(&zero_pattern);
     pattern_map.insert(zero_pattern, 0);
 
-    let nine_pattern = all_patterns.iter().find(|p| { p.len() == 6 }).unwrap().clone();
+    let nine_pattern = all_patterns.iter().find(|p| { p.len() == 6 })?.clone();
     all_patterns.remove(&nine_pattern);
     pattern_map.insert(nine_pattern, 9);
 
-    pattern_map
+    Some(pattern_map)
 }
 
-fn create_mapping_and_calculate(signal: &Signal) -> usize {
-    let mapping = create_mapping(signal);
+fn create_mapping_and_calculate(signal: &Signal) -> Option<usize> {
+    let mapping = create_mapping(signal)?;
 
     let mut place_value = 1;
     let mut total = 0usize;
 
-    for digit in signal.output.iter().rev().map(|digit| mapping.get(digit).unwrap()) {
+    for digit in signal.output.iter().rev() {
+        let digit = mapping.get(digit)?;
         let value = digit * place_value;
         total += value;
         place_value *= 10;
     }
 
-    total
+    Some(total)
 }
 
 #[aoc(day8, part2)]
-fn sum_output_values(signals: &[Signal]) -> usize {
-    signals.iter().map(|s| create_mapping_and_calculate(s)).sum()
-}
\ No newline at end of file
+fn sum_output_values(signals: &[Signal]) -> Result<usize, String> {
+    signals.iter().enumerate().map(|(i, signal)| {
+        create_mapping_and_calculate(signal).ok_or(format!("line {}: patterns don't decode to the ten digits", i + 1))
+    }).sum()
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = Vec<Signal>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        Ok(count_digits_1478(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        sum_output_values(input)
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 8,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(count_digits_1478(&parse_input(EXAMPLE).unwrap()), 26);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(sum_output_values(&parse_input(EXAMPLE).unwrap()), Ok(61229));
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(count_digits_1478(&parse_input(puzzle_input!(8)).unwrap()), 381);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(sum_output_values(&parse_input(puzzle_input!(8)).unwrap()), Ok(1023686));
+    }
+
+    #[test]
+    fn malformed_signals_are_errors() {
+        assert!("ab cd".parse::<Signal>().is_err());
+        assert!("a b c d e f g ab ac ad | ab".parse::<Signal>().is_err());
+        assert!("a b c d e f g ab ac xy | ab ab ab ab".parse::<Signal>().is_err());
+        assert!("a b c d e f g ab ac aa | ab ab ab ab".parse::<Signal>().is_err());
+        assert!("a b c d e f g ab ac ad | ab ab ab ab".parse::<Signal>().is_ok());
+    }
+
+    #[test]
+    fn undecodable_signals_are_errors() {
+        let signals = parse_input(&format!("{}\na b c d e f g ab ac ad | ab ab ab ab", EXAMPLE.lines().next().unwrap())).unwrap();
+        assert_eq!(sum_output_values(&signals), Err("line 2: patterns don't decode to the ten digits".to_string()));
+
+        // every pattern is a digit, but the output shows one that isn't
+        let signals = parse_input("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb ef").unwrap();
+        assert_eq!(sum_output_values(&signals), Err("line 1: patterns don't decode to the ten digits".to_string()));
+    }
+}
@@ -1,10 +1,14 @@
+//! Day 9: Smoke Basin.
+
 use itertools::Itertools;
 use std::collections::HashMap;
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::registry::{self, Day};
+use crate::Solution;
 
-type Point = (usize, usize);
+pub type Point = (usize, usize);
 
-struct HeightMap {
+pub struct HeightMap {
     locations: Vec<Vec<usize>>,
     low_points: Vec<Point>,
     height: usize,
@@ -33,22 +37,34 @@ impl HeightMap {
         low_points
     }
 
-    fn new(locations: Vec<Vec<usize>>) -> Self {
+    pub fn new(locations: Vec<Vec<usize>>) -> Result<Self, &'static str> {
         let height = locations.len();
-        let width = locations.first().unwrap().len();
+        let width = locations.first().map_or(0, |row| row.len());
+
+        if width == 0 {
+            return Err("height map is empty");
+        }
+
+        if locations.iter().any(|row| row.len() != width) {
+            return Err("height map isn't rectangular");
+        }
 
         let low_points = HeightMap::find_low_points(&locations);
 
-        HeightMap {
+        Ok(HeightMap {
             locations,
             low_points,
             height,
             width
-        }
+        })
     }
 
-    fn get(&self, x: usize, y: usize) -> Option<usize> {
-        self.locations.get(y).map(|row| row.get(x).cloned()).flatten()
+    pub fn low_points(&self) -> &[Point] {
+        &self.low_points
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<usize> {
+        self.locations.get(y).and_then(|row| row.get(x).cloned())
     }
 
     fn adjacent_points(&self, x: usize, y: usize) -> Vec<Point> {
@@ -73,29 +89,47 @@ impl HeightMap {
         points
     }
 
-    fn find_basin_low_point(&self, x: usize, y: usize) -> Option<Point> {
-        if let Some(9) = self.get(x, y) {
-            None
-        } else if self.low_points.contains(&(x, y)) {
-            Some((x, y))
-        } else {
-            let points = self.adjacent_points(x, y);
+    /// The low point that rain falling on `(x, y)` drains to, or `None` for
+    /// height 9, which belongs to no basin, and for flat ground with nowhere
+    /// lower to drain to.
+    pub fn find_basin_low_point(&self, mut x: usize, mut y: usize) -> Option<Point> {
+        loop {
+            let height = self.get(x, y)?;
+
+            if height == 9 {
+                return None;
+            } else if self.low_points.contains(&(x, y)) {
+                return Some((x, y));
+            }
+
+            let lowest_adjacent = self.adjacent_points(x, y).into_iter()
+                .min_by_key(|&(x, y)| self.get(x, y))?;
 
-            let lowest_adjacent = *points.iter().sorted_by_key(|&&(x,y)| {
-                self.get(x, y).unwrap()
-            }).next().unwrap();
+            if self.get(lowest_adjacent.0, lowest_adjacent.1)? >= height {
+                return None;
+            }
 
-            self.find_basin_low_point(lowest_adjacent.0, lowest_adjacent.1)
+            (x, y) = lowest_adjacent;
         }
     }
 }
 
 
 #[aoc_generator(day9)]
-fn parse_input(input: &str) -> HeightMap {
-    HeightMap::new(input.lines().map(|line| {
-        line.chars().map(|c| c.to_digit(10).unwrap() as usize).collect()
-    }).collect())
+fn parse_input(input: &str) -> Result<HeightMap, String> {
+    let width = input.lines().next().map_or(0, |l| l.len());
+
+    let locations = input.lines().enumerate().map(|(i, line)| {
+        if line.len() != width {
+            return Err(format!("line {} has {} heights but line 1 has {}", i + 1, line.len(), width));
+        }
+
+        line.chars()
+            .map(|c| c.to_digit(10).map(|d| d as usize).ok_or(format!("line {}: not a height: {:?}", i + 1, c)))
+            .collect()
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    HeightMap::new(locations).map_err(|e| e.to_string())
 }
 
 #[aoc(day9, part1)]
@@ -118,4 +152,81 @@ fn largest_basins_product(height_map: &HeightMap) -> usize {
     }
 
     basin_sizes.values().sorted().rev().take(3).product()
-}
\ No newline at end of file
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Input = HeightMap;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, String> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String> {
+        Ok(sum_of_risk_levels(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String> {
+        Ok(largest_basins_product(input))
+    }
+}
+
+pub const DAY: Day = Day {
+    day: 9,
+    part1: registry::part1::<Puzzle>,
+    part2: Some(registry::part2::<Puzzle>),
+    variants: &[],
+    stream: None
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+2199943210
+3987894921
+9856789892
+8767896789
+9899965678
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(sum_of_risk_levels(&parse_input(EXAMPLE).unwrap()), 15);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(largest_basins_product(&parse_input(EXAMPLE).unwrap()), 1134);
+    }
+
+    #[test]
+    fn part1_input() {
+        assert_eq!(sum_of_risk_levels(&parse_input(puzzle_input!(9)).unwrap()), 425);
+    }
+
+    #[test]
+    fn part2_input() {
+        assert_eq!(largest_basins_product(&parse_input(puzzle_input!(9)).unwrap()), 1135260);
+    }
+
+    #[test]
+    fn flat_ground_drains_nowhere() {
+        let height_map = parse_input("5555\n5515\n5555").unwrap();
+
+        assert_eq!(height_map.find_basin_low_point(0, 2), None);
+        assert_eq!(height_map.find_basin_low_point(1, 1), Some((2, 1)));
+        assert_eq!(largest_basins_product(&height_map), 5);
+    }
+
+    #[test]
+    fn malformed_height_maps_are_errors() {
+        assert_eq!(parse_input("219\n39").err(), Some("line 2 has 2 heights but line 1 has 3".to_string()));
+        assert_eq!(parse_input("219\n3x8").err(), Some("line 2: not a height: 'x'".to_string()));
+        assert_eq!(parse_input("").err(), Some("height map is empty".to_string()));
+    }
+}
@@ -0,0 +1,415 @@
+//! Random puzzle inputs of any size, for stress-testing the solutions. Every
+//! generator is driven by a seeded [`Rng`], so the same seed always gives the
+//! same input, and builds inputs with the same guarantees as the real ones.
+
+use std::fmt::Write;
+use crate::{day11, Solution};
+
+/// xorshift64*, which is plenty for making up puzzle inputs.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 spreads nearby seeds across the whole state, and xorshift
+        // gets stuck at zero, so the state is kept odd
+        let mut z = seed.wrapping_add(0x9E3779B97F4A7C15);
+        z = (z ^ z >> 30).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ z >> 27).wrapping_mul(0x94D049BB133111EB);
+
+        Rng((z ^ z >> 31) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// An index into a slice of length `n`.
+    pub fn index(&mut self, n: usize) -> usize {
+        self.below(n as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+/// Generates input for `day`, with roughly `scale` times as much in it as the
+/// real puzzle input. `None` if there's no generator for that day.
+pub fn input(day: u32, seed: u64, scale: usize) -> Option<String> {
+    let rng = &mut Rng::new(seed);
+    let scale = scale.max(1);
+
+    Some(match day {
+        1 => day1(rng, 2000 * scale),
+        2 => day2(rng, 1000 * scale),
+        3 => day3(rng, 1000 * scale, 12),
+        4 => day4(rng, 100 * scale, 5),
+        5 => day5(rng, 500 * scale, 1000),
+        6 => day6(rng, 300 * scale),
+        7 => day7(rng, 1000 * scale, 2000),
+        8 => day8(rng, 200 * scale),
+        9 => day9(rng, 100 * scale, 100),
+        10 => day10(rng, 90 * scale, 100),
+        11 => day11(rng, 10 * scale, 10),
+        12 => day12(rng, 6 * scale, 3 * scale),
+        13 => day13(rng, 900 * scale, 12),
+        14 => day14(rng, 20 * scale, 10),
+        _ => return None
+    })
+}
+
+/// Sonar depths from a random walk that drifts deeper.
+pub fn day1(rng: &mut Rng, depths: usize) -> String {
+    let mut depth = 1000i64;
+
+    (0..depths).map(|_| {
+        depth = (depth + rng.below(21) as i64 - 8).max(0);
+        depth.to_string()
+    }).collect::<Vec<_>>().join("\n")
+}
+
+pub fn day2(rng: &mut Rng, commands: usize) -> String {
+    (0..commands).map(|_| {
+        let dir = ["forward", "down", "up"][rng.index(3)];
+        format!("{} {}", dir, rng.below(9) + 1)
+    }).collect::<Vec<_>>().join("\n")
+}
+
+pub fn day3(rng: &mut Rng, rows: usize, width: usize) -> String {
+    (0..rows).map(|_| {
+        (0..width).map(|_| if rng.below(2) == 0 { '0' } else { '1' }).collect::<String>()
+    }).collect::<Vec<_>>().join("\n")
+}
+
+/// `boards` square boards of side `size`, none repeating a number, and a draw
+/// order that calls every number, so every board wins eventually.
+pub fn day4(rng: &mut Rng, boards: usize, size: usize) -> String {
+    let cells = size * size;
+    let pool = (cells * 4).max(100);
+
+    let mut numbers = (0..pool).collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+
+    let mut input = numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+    let width = (pool - 1).to_string().len();
+
+    for _ in 0..boards {
+        let mut board = numbers.clone();
+        rng.shuffle(&mut board);
+
+        input.push('\n');
+
+        for row in board[..cells].chunks(size) {
+            let row = row.iter().map(|n| format!("{:>1$}", n, width)).collect::<Vec<_>>();
+            write!(input, "\n{}", row.join(" ")).unwrap();
+        }
+    }
+
+    input
+}
+
+/// Horizontal, vertical and 45° diagonal segments inside a `size` by `size`
+/// box.
+pub fn day5(rng: &mut Rng, segments: usize, size: u64) -> String {
+    (0..segments).map(|_| {
+        let (x1, y1) = (rng.below(size), rng.below(size));
+        let len = rng.below((size / 4).max(1)) + 1;
+
+        let (x2, y2) = match rng.below(3) {
+            0 => (x1, (y1 + len).min(size - 1)),
+            1 => ((x1 + len).min(size - 1), y1),
+            _ => {
+                let len = len.min(size - 1 - x1).min(size - 1 - y1);
+                (x1 + len, y1 + len)
+            }
+        };
+
+        // segments run either way, so flip half of them
+        if rng.below(2) == 0 {
+            format!("{},{} -> {},{}", x1, y1, x2, y2)
+        } else {
+            format!("{},{} -> {},{}", x2, y2, x1, y1)
+        }
+    }).collect::<Vec<_>>().join("\n")
+}
+
+pub fn day6(rng: &mut Rng, fish: usize) -> String {
+    (0..fish).map(|_| (rng.below(5) + 1).to_string()).collect::<Vec<_>>().join(",")
+}
+
+pub fn day7(rng: &mut Rng, crabs: usize, max_position: u64) -> String {
+    (0..crabs).map(|_| rng.below(max_position).to_string()).collect::<Vec<_>>().join(",")
+}
+
+/// Displays with randomly crossed wires. Each shows all ten digits once, so
+/// every one of them can be decoded.
+pub fn day8(rng: &mut Rng, displays: usize) -> String {
+    const DIGITS: [&str; 10] = ["abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg"];
+
+    (0..displays).map(|_| {
+        let mut wires = b"abcdefg".to_vec();
+        rng.shuffle(&mut wires);
+
+        let scramble = |rng: &mut Rng, digit: usize| {
+            let mut pattern = DIGITS[digit].bytes().map(|s| wires[(s - b'a') as usize]).collect::<Vec<_>>();
+            rng.shuffle(&mut pattern);
+            String::from_utf8(pattern).unwrap()
+        };
+
+        let mut order = (0..10).collect::<Vec<_>>();
+        rng.shuffle(&mut order);
+
+        let patterns = order.into_iter().map(|digit| scramble(rng, digit)).collect::<Vec<_>>();
+        let output = (0..4).map(|_| {
+            let digit = rng.index(10);
+            scramble(rng, digit)
+        }).collect::<Vec<_>>();
+
+        format!("{} | {}", patterns.join(" "), output.join(" "))
+    }).collect::<Vec<_>>().join("\n")
+}
+
+/// Basins around low points scattered on a coarse lattice, with height
+/// growing by one per step away from the nearest low point. Every location
+/// has a lower neighbour until it reaches its low point, as in the real input.
+pub fn day9(rng: &mut Rng, width: usize, height: usize) -> String {
+    const SPACING: usize = 8;
+
+    let low_points = (0..height / SPACING).flat_map(|j| (0..width / SPACING).map(move |i| (i, j)))
+        .map(|(i, j)| {
+            let jitter = |rng: &mut Rng| 1 + rng.index(SPACING - 2);
+            (i * SPACING + jitter(rng), j * SPACING + jitter(rng))
+        })
+        .collect::<Vec<_>>();
+
+    (0..height).map(|y| {
+        (0..width).map(|x| {
+            let distance = low_points.iter()
+                .map(|&(lx, ly)| lx.abs_diff(x) + ly.abs_diff(y))
+                .min()
+                .unwrap_or(9);
+
+            char::from(b'0' + distance.min(9) as u8)
+        }).collect::<String>()
+    }).collect::<Vec<_>>().join("\n")
+}
+
+/// Lines of nested brackets, about half with a wrong closing bracket planted
+/// at the end and the rest left incomplete. Nesting stops at `MAX_DEPTH`, as
+/// it does in the real input, so autocomplete scores fit in a `usize`.
+pub fn day10(rng: &mut Rng, lines: usize, length: usize) -> String {
+    const PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+    const MAX_DEPTH: usize = 20;
+
+    (0..lines).map(|_| {
+        let mut line = String::new();
+        let mut open = vec![];
+
+        for _ in 0..length {
+            if open.is_empty() || (open.len() < MAX_DEPTH && rng.below(2) == 0) {
+                let pair = PAIRS[rng.index(PAIRS.len())];
+                line.push(pair.0);
+                open.push(pair.1);
+            } else {
+                line.push(open.pop().unwrap());
+            }
+        }
+
+        if open.is_empty() {
+            let pair = PAIRS[rng.index(PAIRS.len())];
+            line.push(pair.0);
+            open.push(pair.1);
+        }
+
+        if rng.below(2) == 0 {
+            let expected = *open.last().unwrap();
+            let wrong = PAIRS.iter().map(|p| p.1).filter(|&c| c != expected).collect::<Vec<_>>();
+            line.push(wrong[rng.index(wrong.len())]);
+        }
+
+        line
+    }).collect::<Vec<_>>().join("\n")
+}
+
+/// Octopus energy levels that flash in sync sooner or later, like the real
+/// ones. Random grids often never do, especially big ones, so each grid is run
+/// until it syncs or repeats itself, and each retry draws its levels from a
+/// narrower band. A grid with every octopus at the same level flashes in sync
+/// once they reach 9, so the retries always end.
+pub fn day11(rng: &mut Rng, width: usize, height: usize) -> String {
+    let mut spread = 10;
+
+    loop {
+        let low = rng.below(11 - spread);
+
+        let grid = (0..height).map(|_| {
+            (0..width).map(|_| char::from(b'0' + (low + rng.below(spread)) as u8)).collect::<String>()
+        }).collect::<Vec<_>>().join("\n");
+
+        if spread == 1 || day11::Puzzle::parse(&grid).and_then(|g| day11::Puzzle::part2(&g)).is_ok() {
+            return grid;
+        }
+
+        spread -= 1;
+    }
+}
+
+/// A connected cave system with `small` small caves and `big` big ones. Big
+/// caves are never joined to each other, which would allow endless paths.
+pub fn day12(rng: &mut Rng, small: usize, big: usize) -> String {
+    let mut caves = vec!["start".to_string(), "end".to_string()];
+    caves.extend((0..small).map(|i| cave_name(i, false)));
+    caves.extend((0..big).map(|i| cave_name(i, true)));
+
+    let is_big = |cave: usize| caves[cave].chars().all(|c| c.is_ascii_uppercase());
+    let can_join = |a: usize, b: usize| a != b && !(is_big(a) && is_big(b));
+
+    let mut order = (1..caves.len()).collect::<Vec<_>>();
+    rng.shuffle(&mut order);
+    order.insert(0, 0);
+
+    let mut edges: Vec<(usize, usize)> = vec![];
+
+    // join each cave to one placed before it, so everything is reachable from
+    // start, which is small and so always a candidate
+    for (placed, &cave) in order.iter().enumerate().skip(1) {
+        let candidates = order[..placed].iter().filter(|&&other| can_join(cave, other)).collect::<Vec<_>>();
+        edges.push((*candidates[rng.index(candidates.len())], cave));
+    }
+
+    for _ in 0..caves.len() {
+        let (a, b) = (rng.index(caves.len()), rng.index(caves.len()));
+        let joined = edges.contains(&(a, b)) || edges.contains(&(b, a));
+
+        if can_join(a, b) && !joined {
+            edges.push((a, b));
+        }
+    }
+
+    edges.iter().map(|&(a, b)| format!("{}-{}", caves[a], caves[b])).collect::<Vec<_>>().join("\n")
+}
+
+fn cave_name(mut i: usize, big: bool) -> String {
+    let base = if big { b'A' } else { b'a' };
+    let mut name = vec![];
+
+    loop {
+        name.push(base + (i % 26) as u8);
+        i /= 26;
+
+        if name.len() >= 2 && i == 0 {
+            break;
+        }
+    }
+
+    String::from_utf8(name).unwrap()
+}
+
+/// A transparent sheet that folds down to a 40 by 6 strip, like the real one.
+/// The dots are placed on the strip and then unfolded at random, so none ever
+/// lands on a fold line.
+pub fn day13(rng: &mut Rng, dots: usize, folds: usize) -> String {
+    let (mut width, mut height) = (40, 6);
+    let mut instructions = vec![];
+
+    let mut points = (0..dots).map(|_| (rng.index(width), rng.index(height))).collect::<Vec<_>>();
+
+    for _ in 0..folds {
+        let along_x = rng.below(2) == 0;
+        let line = if along_x { width } else { height };
+
+        for (x, y) in points.iter_mut() {
+            let c = if along_x { x } else { y };
+
+            if rng.below(2) == 0 {
+                *c = 2 * line - *c;
+            }
+        }
+
+        if along_x {
+            width = 2 * width + 1;
+            instructions.push(format!("fold along x={}", line));
+        } else {
+            height = 2 * height + 1;
+            instructions.push(format!("fold along y={}", line));
+        }
+    }
+
+    instructions.reverse();
+
+    let mut input = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect::<Vec<_>>().join("\n");
+    write!(input, "\n\n{}", instructions.join("\n")).unwrap();
+
+    input
+}
+
+/// A template and a rule for every pair of `elements` distinct elements.
+pub fn day14(rng: &mut Rng, template: usize, elements: usize) -> String {
+    let elements = (b'A'..=b'Z').take(elements.clamp(1, 26)).map(char::from).collect::<Vec<_>>();
+
+    let mut input = (0..template).map(|_| elements[rng.index(elements.len())]).collect::<String>();
+    input.push('\n');
+
+    for &a in &elements {
+        for &b in &elements {
+            write!(input, "\n{}{} -> {}", a, b, elements[rng.index(elements.len())]).unwrap();
+        }
+    }
+
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn same_seed_same_input() {
+        for day in 1..=14 {
+            assert_eq!(input(day, 7, 1), input(day, 7, 1), "day {}", day);
+            assert_ne!(input(day, 7, 1), input(day, 8, 1), "day {}", day);
+        }
+    }
+
+    #[test]
+    fn seeds_differing_in_the_low_bit_differ() {
+        assert_ne!(Rng::new(2).next_u64(), Rng::new(3).next_u64());
+        assert_ne!(input(1, 2, 1), input(1, 3, 1));
+    }
+
+    #[test]
+    fn generated_inputs_solve() {
+        for day in registry::DAYS {
+            let input = input(day.day, 2021, 1).unwrap();
+
+            (day.part1)(&input).unwrap_or_else(|e| panic!("day {} part 1: {}", day.day, e));
+
+            if let Some(part2) = day.part2 {
+                part2(&input).unwrap_or_else(|e| panic!("day {} part 2: {}", day.day, e));
+            }
+        }
+    }
+
+    #[test]
+    fn octopus_grids_sync() {
+        for seed in 0..8 {
+            for scale in [1, 2] {
+                let grid = input(11, seed, scale).unwrap();
+                let grid = day11::Puzzle::parse(&grid).unwrap();
+
+                assert!(day11::Puzzle::part2(&grid).is_ok(), "seed {} scale {}", seed, scale);
+            }
+        }
+    }
+}
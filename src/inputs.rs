@@ -0,0 +1,244 @@
+//! Puzzle inputs on disk, downloaded on first use.
+//!
+//! Inputs are cached as `<dir>/<year>/dayN.txt`, which by default is where the
+//! checked-in ones already live. A missing or truncated input is downloaded
+//! from the [`Server`] at `/<year>/day/N/input`.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::server::{self, Server};
+
+pub const DEFAULT_DIR: &str = "input";
+
+#[derive(Debug)]
+pub enum InputError {
+    /// The input has to be downloaded, but there's no session token to do it.
+    NoSession { day: u32 },
+    /// The server didn't hand over the input.
+    Http { day: u32, message: String },
+    /// The input is cut short, so it can't be trusted.
+    Truncated { day: u32, reason: &'static str },
+    Io { path: PathBuf, error: io::Error }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::NoSession { day } =>
+                write!(f, "day {} input isn't cached, and AOC_SESSION isn't set to download it", day),
+            InputError::Http { day, message } =>
+                write!(f, "couldn't download day {} input: {}", day, message),
+            InputError::Truncated { day, reason } =>
+                write!(f, "day {} input looks truncated: {}", day, reason),
+            InputError::Io { path, error } =>
+                write!(f, "{}: {}", path.display(), error)
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Where inputs are cached, and where to download them from.
+#[derive(Debug, Clone)]
+pub struct Inputs {
+    pub year: u32,
+    pub dir: PathBuf,
+    pub server: Server
+}
+
+impl Inputs {
+    pub fn new(year: u32) -> Self {
+        Inputs {
+            year,
+            dir: PathBuf::from(DEFAULT_DIR),
+            server: Server::default()
+        }
+    }
+
+    /// Settings from the environment: `AOC_INPUT_DIR`, and the server's
+    /// settings, with the defaults for any that aren't set.
+    pub fn from_env(year: u32) -> Self {
+        Inputs {
+            year,
+            dir: server::env_var("AOC_INPUT_DIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from(DEFAULT_DIR)),
+            server: Server::from_env()
+        }
+    }
+
+    pub fn path(&self, day: u32) -> PathBuf {
+        self.dir.join(self.year.to_string()).join(format!("day{}.txt", day))
+    }
+
+    /// The day's input, downloading it first if it isn't cached or the cached
+    /// copy is truncated.
+    pub fn load(&self, day: u32) -> Result<String, InputError> {
+        let path = self.path(day);
+
+        match fs::read_to_string(&path) {
+            Ok(input) => match check(day, &input) {
+                Ok(()) => Ok(input),
+                // without a session there's nothing to replace it with
+                Err(e) if self.server.session.is_none() => Err(e),
+                Err(_) => self.fetch(day)
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => self.fetch(day),
+            Err(error) => Err(io_error(&path)(error))
+        }
+    }
+
+    /// Downloads the day's input and caches it, replacing any cached copy.
+    pub fn fetch(&self, day: u32) -> Result<String, InputError> {
+        let session = self.server.session.as_ref().ok_or(InputError::NoSession { day })?;
+
+        let input = self.server.get(&format!("/{}/day/{}/input", self.year, day), session)
+            .map_err(|message| InputError::Http { day, message })?;
+
+        check(day, &input)?;
+        self.store(day, &input)?;
+
+        Ok(input)
+    }
+
+    /// Writes to a temporary file first, so an interrupted write never leaves
+    /// a partial input behind.
+    fn store(&self, day: u32, input: &str) -> Result<(), InputError> {
+        let path = self.path(day);
+        let partial = path.with_extension("txt.partial");
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(io_error(dir))?;
+        }
+
+        fs::write(&partial, input).map_err(io_error(&partial))?;
+        fs::rename(&partial, &path).map_err(io_error(&path))
+    }
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> InputError {
+    let path = path.to_path_buf();
+    move |error| InputError::Io { path, error }
+}
+
+/// Puzzle inputs always end with a newline, so one that doesn't was cut short.
+pub fn check(day: u32, input: &str) -> Result<(), InputError> {
+    if input.is_empty() {
+        Err(InputError::Truncated { day, reason: "it's empty" })
+    } else if !input.ends_with('\n') {
+        Err(InputError::Truncated { day, reason: "it doesn't end with a newline" })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockServer, Request};
+
+    /// A directory under the system's temp dir, removed when the test is done
+    /// with it, even if the test fails.
+    struct ScratchDir(PathBuf);
+
+    impl std::ops::Deref for ScratchDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn scratch_dir(name: &str) -> ScratchDir {
+        let dir = std::env::temp_dir().join(format!("advent-2021-inputs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ScratchDir(dir)
+    }
+
+    fn inputs(server: &MockServer, dir: &Path, session: Option<&str>) -> Inputs {
+        Inputs {
+            year: 2021,
+            dir: dir.to_path_buf(),
+            server: Server {
+                base_url: server.base_url.clone(),
+                session: session.map(String::from)
+            }
+        }
+    }
+
+    #[test]
+    fn downloads_missing_input_once() {
+        let server = MockServer::start(vec![(200, "1\n2\n3\n")]);
+        let dir = scratch_dir("download");
+        let inputs = inputs(&server, &dir, Some("abc123"));
+
+        assert_eq!(inputs.load(1).unwrap(), "1\n2\n3\n");
+        assert_eq!(fs::read_to_string(dir.join("2021/day1.txt")).unwrap(), "1\n2\n3\n");
+
+        // the second load comes from the cache
+        assert_eq!(inputs.load(1).unwrap(), "1\n2\n3\n");
+        assert_eq!(server.requests(), vec![Request {
+            method: "GET".to_string(),
+            path: "/2021/day/1/input".to_string(),
+            cookie: Some("session=abc123".to_string()),
+            body: String::new()
+        }]);
+    }
+
+    #[test]
+    fn replaces_truncated_cached_input() {
+        let server = MockServer::start(vec![(200, "forward 5\ndown 5\n")]);
+        let dir = scratch_dir("truncated-cache");
+        let inputs = inputs(&server, &dir, Some("abc123"));
+
+        fs::create_dir_all(dir.join("2021")).unwrap();
+        fs::write(dir.join("2021/day2.txt"), "forward 5\ndo").unwrap();
+
+        assert_eq!(inputs.load(2).unwrap(), "forward 5\ndown 5\n");
+        assert_eq!(fs::read_to_string(dir.join("2021/day2.txt")).unwrap(), "forward 5\ndown 5\n");
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn rejects_truncated_download() {
+        let server = MockServer::start(vec![(200, "1\n2\n3")]);
+        let dir = scratch_dir("truncated-download");
+        let inputs = inputs(&server, &dir, Some("abc123"));
+
+        assert!(matches!(inputs.load(1), Err(InputError::Truncated { day: 1, .. })));
+        assert!(!dir.join("2021/day1.txt").exists());
+    }
+
+    #[test]
+    fn reports_server_errors() {
+        let server = MockServer::start(vec![(400, "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n")]);
+        let dir = scratch_dir("server-error");
+        let inputs = inputs(&server, &dir, Some("expired"));
+
+        match inputs.load(3) {
+            Err(InputError::Http { day: 3, message }) => assert!(message.starts_with("400 Puzzle inputs differ by user."), "{}", message),
+            other => panic!("expected an HTTP error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn needs_session_only_to_download() {
+        let dir = scratch_dir("no-session");
+        let inputs = Inputs { dir: dir.to_path_buf(), ..Inputs::new(2021) };
+
+        assert!(matches!(inputs.load(4), Err(InputError::NoSession { day: 4 })));
+
+        fs::create_dir_all(dir.join("2021")).unwrap();
+        fs::write(dir.join("2021/day4.txt"), "7,4,9\n").unwrap();
+        assert_eq!(inputs.load(4).unwrap(), "7,4,9\n");
+
+        fs::write(dir.join("2021/day4.txt"), "7,4,9").unwrap();
+        assert!(matches!(inputs.load(4), Err(InputError::Truncated { day: 4, .. })));
+    }
+}
@@ -0,0 +1,400 @@
+//! The answers the server has accepted for each part, and the ones it has
+//! turned down.
+//!
+//! The ledger is a JSON file, `answers.json` by default, keyed by day and then
+//! part. Runs on the real puzzle input are checked against it, and an answer
+//! it already knows is wrong is never sent to the server again.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::server::{self, Server};
+use crate::Answer;
+
+pub const DEFAULT_PATH: &str = "answers.json";
+
+#[derive(Debug)]
+pub enum LedgerError {
+    Io { path: PathBuf, error: io::Error },
+    /// The ledger file isn't a ledger.
+    Corrupt { path: PathBuf, message: String },
+    /// The part is already solved, so there's nothing left to submit.
+    Solved { day: u32, part: u32, accepted: String },
+    /// The server has already turned this answer down.
+    KnownWrong { day: u32, part: u32, answer: String },
+    /// The answer is a drawing, which has to be read before it's submitted.
+    Unreadable { day: u32, part: u32 },
+    NoSession,
+    /// The answer didn't reach the server, or its reply couldn't be read.
+    Http { message: String },
+    /// The server replied, but without saying whether the answer was right.
+    Unrecognized { reply: String }
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::Io { path, error } =>
+                write!(f, "{}: {}", path.display(), error),
+            LedgerError::Corrupt { path, message } =>
+                write!(f, "{} isn't a valid ledger: {}", path.display(), message),
+            LedgerError::Solved { day, part, accepted } =>
+                write!(f, "day {} part {} is already solved, with {:?}", day, part, accepted),
+            LedgerError::KnownWrong { day, part, answer } =>
+                write!(f, "{:?} was already turned down for day {} part {}", answer, day, part),
+            LedgerError::Unreadable { day, part } =>
+                write!(f, "day {} part {} answered with a drawing; read it and submit it with --answer", day, part),
+            LedgerError::NoSession =>
+                write!(f, "AOC_SESSION isn't set, so there's no one to submit the answer as"),
+            LedgerError::Http { message } =>
+                write!(f, "couldn't submit the answer: {}", message),
+            LedgerError::Unrecognized { reply } =>
+                write!(f, "couldn't tell what the server made of the answer: {:?}", reply)
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
+
+/// Everything known about the answers to one part.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepted: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wrong: Vec<String>
+}
+
+/// How an answer compares with what the ledger knows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Status {
+    /// It's the accepted answer.
+    Pass,
+    /// It isn't the accepted answer, or it's one the server turned down.
+    Fail {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expected: Option<String>
+    },
+    /// Nothing's known about it yet.
+    New
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail { expected: Some(expected) } => write!(f, "fail, expected {}", expected),
+            Status::Fail { expected: None } => write!(f, "fail, known wrong"),
+            Status::New => write!(f, "new")
+        }
+    }
+}
+
+/// What the server made of a submitted answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Right,
+    /// Wrong, sometimes with a hint that it's "too high" or "too low".
+    Wrong { hint: Option<&'static str> },
+    /// Answers are rate limited, so this one wasn't looked at.
+    TooSoon { wait: Option<String> },
+    /// The server isn't taking answers for this part, usually because it's
+    /// already solved.
+    WrongLevel
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Right => write!(f, "that's the right answer"),
+            Verdict::Wrong { hint: Some(hint) } => write!(f, "that's not the right answer, it's {}", hint),
+            Verdict::Wrong { hint: None } => write!(f, "that's not the right answer"),
+            Verdict::TooSoon { wait: Some(wait) } => write!(f, "answered too recently, {} left to wait", wait),
+            Verdict::TooSoon { wait: None } => write!(f, "answered too recently"),
+            Verdict::WrongLevel => write!(f, "the server isn't taking answers for this part; is it already solved?")
+        }
+    }
+}
+
+impl Verdict {
+    /// Reads the verdict out of the page the server replies with.
+    pub fn from_reply(reply: &str) -> Option<Verdict> {
+        if reply.contains("That's the right answer") {
+            Some(Verdict::Right)
+        } else if reply.contains("That's not the right answer") {
+            let hint = ["too high", "too low"].into_iter().find(|hint| reply.contains(hint));
+            Some(Verdict::Wrong { hint })
+        } else if reply.contains("You gave an answer too recently") {
+            let wait = reply.split_once("You have ")
+                .and_then(|(_, rest)| rest.split_once(" left to wait"))
+                .map(|(wait, _)| wait.to_string());
+
+            Some(Verdict::TooSoon { wait })
+        } else if reply.contains("You don't seem to be solving the right level") {
+            Some(Verdict::WrongLevel)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Ledger {
+    pub year: u32,
+    pub path: PathBuf,
+    days: BTreeMap<u32, BTreeMap<u32, Record>>
+}
+
+impl Ledger {
+    /// The ledger at `path`, which starts out empty if there isn't one yet.
+    pub fn open(year: u32, path: impl Into<PathBuf>) -> Result<Self, LedgerError> {
+        let path = path.into();
+
+        let days = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| LedgerError::Corrupt { path: path.clone(), message: e.to_string() })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(error) => return Err(LedgerError::Io { path, error })
+        };
+
+        Ok(Ledger { year, path, days })
+    }
+
+    /// The ledger at `AOC_LEDGER`, or at [`DEFAULT_PATH`] if that isn't set.
+    pub fn from_env(year: u32) -> Result<Self, LedgerError> {
+        Ledger::open(year, server::env_var("AOC_LEDGER").unwrap_or_else(|| DEFAULT_PATH.to_string()))
+    }
+
+    pub fn record(&self, day: u32, part: u32) -> Option<&Record> {
+        self.days.get(&day).and_then(|parts| parts.get(&part))
+    }
+
+    fn record_mut(&mut self, day: u32, part: u32) -> &mut Record {
+        self.days.entry(day).or_default().entry(part).or_default()
+    }
+
+    pub fn check(&self, day: u32, part: u32, answer: &Answer) -> Status {
+        let answer = answer.to_string();
+
+        match self.record(day, part) {
+            Some(Record { accepted: Some(accepted), .. }) if *accepted == answer => Status::Pass,
+            Some(Record { accepted: Some(accepted), .. }) => Status::Fail { expected: Some(accepted.clone()) },
+            Some(record) if record.wrong.contains(&answer) => Status::Fail { expected: None },
+            _ => Status::New
+        }
+    }
+
+    /// Submits the answer unless the ledger already knows how that would go,
+    /// and records the verdict.
+    pub fn submit(&mut self, server: &Server, day: u32, part: u32, answer: &Answer) -> Result<Verdict, LedgerError> {
+        if let Answer::Grid(_) = answer {
+            return Err(LedgerError::Unreadable { day, part });
+        }
+
+        let answer = answer.to_string();
+
+        if let Some(record) = self.record(day, part) {
+            if let Some(accepted) = &record.accepted {
+                return Err(LedgerError::Solved { day, part, accepted: accepted.clone() });
+            }
+
+            if record.wrong.contains(&answer) {
+                return Err(LedgerError::KnownWrong { day, part, answer });
+            }
+        }
+
+        let session = server.session.as_ref().ok_or(LedgerError::NoSession)?;
+        let level = part.to_string();
+
+        let reply = server.post(&format!("/{}/day/{}/answer", self.year, day), session, &[("level", &level), ("answer", &answer)])
+            .map_err(|message| LedgerError::Http { message })?;
+
+        let verdict = Verdict::from_reply(&reply)
+            .ok_or_else(|| LedgerError::Unrecognized { reply: article_text(&reply) })?;
+
+        match verdict {
+            Verdict::Right => self.record_mut(day, part).accepted = Some(answer),
+            Verdict::Wrong { .. } => self.record_mut(day, part).wrong.push(answer),
+            Verdict::TooSoon { .. } | Verdict::WrongLevel => return Ok(verdict)
+        }
+
+        self.save()?;
+        Ok(verdict)
+    }
+
+    /// Writes to a temporary file first, so an interrupted write never loses
+    /// the answers already recorded.
+    pub fn save(&self) -> Result<(), LedgerError> {
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |error| LedgerError::Io { path, error }
+        };
+
+        let mut json = serde_json::to_string_pretty(&self.days)
+            .map_err(|e| LedgerError::Corrupt { path: self.path.clone(), message: e.to_string() })?;
+        json.push('\n');
+
+        let partial = self.path.with_extension("json.partial");
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(io_error(dir))?;
+        }
+
+        fs::write(&partial, json).map_err(io_error(&partial))?;
+        fs::rename(&partial, &self.path).map_err(io_error(&self.path))
+    }
+}
+
+/// The text of the reply's `<article>`, or of the whole reply if it hasn't
+/// got one, without any markup.
+fn article_text(reply: &str) -> String {
+    let article = reply.split_once("<article>")
+        .and_then(|(_, rest)| rest.split_once("</article>"))
+        .map_or(reply, |(article, _)| article);
+
+    let mut text = String::new();
+    let mut in_tag = false;
+
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    const RIGHT: &str = "<main><article><p>That's the right answer!  You are <em>one gold star</em> closer to saving Christmas.</p></article></main>";
+    const TOO_HIGH: &str = "<main><article><p>That's not the right answer; your answer is too high.  Please wait one minute before trying again.</p></article></main>";
+    const TOO_SOON: &str = "<main><article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 41s left to wait.</p></article></main>";
+
+    fn scratch_ledger(name: &str) -> Ledger {
+        let path = std::env::temp_dir().join(format!("advent-2021-ledger-{}-{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        Ledger::open(2021, path).unwrap()
+    }
+
+    fn server(mock: &MockServer) -> Server {
+        Server { base_url: mock.base_url.clone(), session: Some("abc123".to_string()) }
+    }
+
+    #[test]
+    fn checks_answers_against_the_ledger() {
+        let mut ledger = scratch_ledger("check");
+        ledger.record_mut(1, 1).accepted = Some("1233".to_string());
+        ledger.record_mut(1, 2).wrong.push("1200".to_string());
+
+        assert_eq!(ledger.check(1, 1, &Answer::Number(1233)), Status::Pass);
+        assert_eq!(ledger.check(1, 1, &Answer::Number(1232)), Status::Fail { expected: Some("1233".to_string()) });
+        assert_eq!(ledger.check(1, 2, &Answer::Number(1200)), Status::Fail { expected: None });
+        assert_eq!(ledger.check(1, 2, &Answer::Number(1275)), Status::New);
+        assert_eq!(ledger.check(2, 1, &Answer::Number(1)), Status::New);
+    }
+
+    #[test]
+    fn saved_ledgers_open_the_same() {
+        let mut ledger = scratch_ledger("roundtrip");
+        ledger.record_mut(13, 2).accepted = Some("RCPLAKHL".to_string());
+        ledger.record_mut(13, 1).wrong.extend(["17".to_string(), "742".to_string()]);
+        ledger.save().unwrap();
+
+        let reopened = Ledger::open(2021, &ledger.path).unwrap();
+        assert_eq!(reopened.days, ledger.days);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&fs::read_to_string(&ledger.path).unwrap()).unwrap(),
+            serde_json::json!({ "13": { "1": { "wrong": ["17", "742"] }, "2": { "accepted": "RCPLAKHL" } } })
+        );
+
+        fs::remove_file(&ledger.path).unwrap();
+    }
+
+    #[test]
+    fn reads_verdicts_from_replies() {
+        assert_eq!(Verdict::from_reply(RIGHT), Some(Verdict::Right));
+        assert_eq!(Verdict::from_reply(TOO_HIGH), Some(Verdict::Wrong { hint: Some("too high") }));
+        assert_eq!(Verdict::from_reply(TOO_SOON), Some(Verdict::TooSoon { wait: Some("41s".to_string()) }));
+        assert_eq!(Verdict::from_reply("<article><p>You don't seem to be solving the right level.  Did you already complete it?</p></article>"), Some(Verdict::WrongLevel));
+        assert_eq!(Verdict::from_reply("<html>Maintenance</html>"), None);
+    }
+
+    #[test]
+    fn records_wrong_answers_and_never_resubmits_them() {
+        let mock = MockServer::start(vec![(200, TOO_HIGH), (200, RIGHT)]);
+        let mut ledger = scratch_ledger("wrong");
+
+        assert_eq!(ledger.submit(&server(&mock), 7, 1, &Answer::Number(400000)).unwrap(), Verdict::Wrong { hint: Some("too high") });
+        assert!(matches!(ledger.submit(&server(&mock), 7, 1, &Answer::Number(400000)), Err(LedgerError::KnownWrong { day: 7, part: 1, .. })));
+
+        // the turned-down answer is remembered across runs
+        let mut ledger = Ledger::open(2021, &ledger.path).unwrap();
+        assert_eq!(ledger.check(7, 1, &Answer::Number(400000)), Status::Fail { expected: None });
+        assert_eq!(ledger.submit(&server(&mock), 7, 1, &Answer::Number(354129)).unwrap(), Verdict::Right);
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/2021/day/7/answer");
+        assert_eq!(requests[0].cookie.as_deref(), Some("session=abc123"));
+        assert_eq!(requests[0].body, "level=1&answer=400000");
+        assert_eq!(requests[1].body, "level=1&answer=354129");
+
+        fs::remove_file(&ledger.path).unwrap();
+    }
+
+    #[test]
+    fn solved_parts_are_not_resubmitted() {
+        let mock = MockServer::start(vec![(200, RIGHT)]);
+        let mut ledger = scratch_ledger("solved");
+
+        assert_eq!(ledger.submit(&server(&mock), 13, 2, &Answer::Text("RCPLAKHL".to_string())).unwrap(), Verdict::Right);
+        assert_eq!(ledger.check(13, 2, &Answer::Text("RCPLAKHL".to_string())), Status::Pass);
+
+        for answer in ["RCPLAKHL", "RCPLAKHI"] {
+            assert!(matches!(
+                ledger.submit(&server(&mock), 13, 2, &Answer::Text(answer.to_string())),
+                Err(LedgerError::Solved { day: 13, part: 2, accepted }) if accepted == "RCPLAKHL"
+            ));
+        }
+
+        assert_eq!(mock.requests().len(), 1);
+        fs::remove_file(&ledger.path).unwrap();
+    }
+
+    #[test]
+    fn rate_limited_answers_are_not_recorded() {
+        let mock = MockServer::start(vec![(200, TOO_SOON), (200, "<main><article><p>Down for maintenance</p></article></main>")]);
+        let mut ledger = scratch_ledger("too-soon");
+
+        assert_eq!(ledger.submit(&server(&mock), 3, 2, &Answer::Number(4996233)).unwrap(), Verdict::TooSoon { wait: Some("41s".to_string()) });
+        assert_eq!(ledger.check(3, 2, &Answer::Number(4996233)), Status::New);
+
+        match ledger.submit(&server(&mock), 3, 2, &Answer::Number(4996233)) {
+            Err(LedgerError::Unrecognized { reply }) => assert_eq!(reply, "Down for maintenance"),
+            other => panic!("expected an unrecognized reply, got {:?}", other)
+        }
+
+        assert_eq!(ledger.check(3, 2, &Answer::Number(4996233)), Status::New);
+        assert!(!ledger.path.exists());
+    }
+
+    #[test]
+    fn drawings_and_missing_sessions_never_reach_the_server() {
+        let mut ledger = scratch_ledger("offline");
+        let server = Server { base_url: "http://127.0.0.1:9".to_string(), session: None };
+
+        assert!(matches!(ledger.submit(&server, 13, 2, &Answer::Grid(vec!["#..#".to_string()])), Err(LedgerError::Unreadable { day: 13, part: 2 })));
+        assert!(matches!(ledger.submit(&server, 1, 1, &Answer::Number(1233)), Err(LedgerError::NoSession)));
+    }
+}
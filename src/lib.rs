@@ -1,18 +1,41 @@
+//! Solutions to Advent of Code 2021. Each `dayN` module has a `Puzzle` that
+//! implements [`Solution`], alongside the types it's built from.
+
 use aoc_runner_derive::aoc_lib;
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
+/// A checked-in puzzle input as the runner hands it over, without trailing newlines.
+#[cfg(test)]
+macro_rules! puzzle_input {
+    ($day:literal) => {
+        include_str!(concat!("../input/2021/day", $day, ".txt")).trim_end_matches('\n')
+    };
+}
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+
+pub mod generate;
+pub mod inputs;
+pub mod ledger;
+#[cfg(test)]
+mod mock_server;
+mod ocr;
+pub mod registry;
+pub mod server;
+mod solution;
+
+pub use solution::{Answer, Solution, MAX_JSON_INTEGER};
 
 aoc_lib!{ year = 2021 }
\ No newline at end of file
@@ -1,6 +1,368 @@
-extern crate aoc_runner_derive;
-extern crate advent_2021;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::process::exit;
+use std::time::Duration;
+use advent_2021::{generate, Answer};
+use advent_2021::inputs::Inputs;
+use advent_2021::ledger::{Ledger, Status};
+use advent_2021::registry::{self, Report, Run, StreamSolver, DEFAULT_VARIANT};
+use advent_2021::server::Server;
 
-use aoc_runner_derive::aoc_main;
+const YEAR: u32 = 2021;
 
-aoc_main! { lib = advent_2021 }
\ No newline at end of file
+const USAGE: &str = "\
+usage: advent-2021 run --day N [--part P [--variant NAME]] [--input PATH|-] [--json] [--time]
+       advent-2021 submit --day N --part P [--answer TEXT]
+       advent-2021 fetch [--day N] [--force]
+       advent-2021 generate --day N [--seed S] [--scale K]
+
+run solves one part of one day, or every part if --part is left out. Input is
+read from PATH, from stdin if PATH is -, and from input/2021/dayN.txt by
+default, which is downloaded if it's missing or truncated. --variant picks one
+of the part's alternative solutions instead of the default one. --time reports
+how long parsing and solving took for each part. --json prints one JSON object
+per part with its answer, timing and variant, and a drawing of the answer for
+parts that have one.
+
+Answers to the real input are checked against the ledger of accepted and
+turned-down answers in answers.json (or AOC_LEDGER), and marked pass, fail or
+new. run fails if any answer doesn't match an accepted one.
+
+submit sends an answer to the server, and records in the ledger whether it was
+right. The answer is the part's solution to the real input unless --answer
+gives one. Parts that are already solved, and answers that were already turned
+down, are never sent.
+
+fetch downloads the input for one day, or for every solved day, unless it's
+already cached. --force downloads it again anyway. Downloads need the session
+cookie of a logged-in user in AOC_SESSION, as do submissions. AOC_BASE_URL
+sets the server to download from and submit to, and AOC_INPUT_DIR the
+directory to cache inputs in (input by default).
+
+generate prints a random input for a day, about K times the size of the real
+one (1 by default). The same seed (2021 by default) always gives the same
+input.";
+
+enum Command {
+    Run(RunArgs),
+    Submit(SubmitArgs),
+    Fetch(FetchArgs),
+    Generate(GenerateArgs)
+}
+
+struct RunArgs {
+    day: u32,
+    part: Option<u32>,
+    variant: Option<String>,
+    input: Option<String>,
+    json: bool,
+    time: bool
+}
+
+fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+    let mut day = None;
+    let mut part = None;
+    let mut variant = None;
+    let mut input = None;
+    let mut json = false;
+    let mut time = false;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--day" => day = Some(value()?.parse::<u32>().map_err(|_| "--day must be a number")?),
+            "--part" => part = Some(value()?.parse::<u32>().map_err(|_| "--part must be a number")?),
+            "--variant" => variant = Some(value()?.clone()),
+            "--input" => input = Some(value()?.clone()),
+            "--json" => json = true,
+            "--time" => time = true,
+            _ => return Err(format!("unexpected argument {}", arg))
+        }
+    }
+
+    if variant.is_some() && part.is_none() {
+        return Err("--variant needs --part".to_string());
+    }
+
+    Ok(RunArgs {
+        day: day.ok_or("--day is required")?,
+        part,
+        variant,
+        input,
+        json,
+        time
+    })
+}
+
+struct SubmitArgs {
+    day: u32,
+    part: u32,
+    answer: Option<String>
+}
+
+fn parse_submit_args(args: &[String]) -> Result<SubmitArgs, String> {
+    let mut day = None;
+    let mut part = None;
+    let mut answer = None;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--day" => day = Some(value()?.parse::<u32>().map_err(|_| "--day must be a number")?),
+            "--part" => part = Some(value()?.parse::<u32>().map_err(|_| "--part must be a number")?),
+            "--answer" => answer = Some(value()?.trim().to_string()),
+            _ => return Err(format!("unexpected argument {}", arg))
+        }
+    }
+
+    Ok(SubmitArgs {
+        day: day.ok_or("--day is required")?,
+        part: part.ok_or("--part is required")?,
+        answer
+    })
+}
+
+struct FetchArgs {
+    day: Option<u32>,
+    force: bool
+}
+
+fn parse_fetch_args(args: &[String]) -> Result<FetchArgs, String> {
+    let mut day = None;
+    let mut force = false;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--day" => day = Some(value()?.parse::<u32>().map_err(|_| "--day must be a number")?),
+            "--force" => force = true,
+            _ => return Err(format!("unexpected argument {}", arg))
+        }
+    }
+
+    Ok(FetchArgs { day, force })
+}
+
+struct GenerateArgs {
+    day: u32,
+    seed: u64,
+    scale: usize
+}
+
+fn parse_generate_args(args: &[String]) -> Result<GenerateArgs, String> {
+    let mut day = None;
+    let mut seed = 2021;
+    let mut scale = 1;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--day" => day = Some(value()?.parse::<u32>().map_err(|_| "--day must be a number")?),
+            "--seed" => seed = value()?.parse::<u64>().map_err(|_| "--seed must be a number")?,
+            "--scale" => scale = value()?.parse::<usize>().map_err(|_| "--scale must be a number")?,
+            _ => return Err(format!("unexpected argument {}", arg))
+        }
+    }
+
+    Ok(GenerateArgs {
+        day: day.ok_or("--day is required")?,
+        seed,
+        scale
+    })
+}
+
+fn read_input(args: &RunArgs) -> Result<String, String> {
+    let input = match args.input.as_deref() {
+        Some("-") => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input).map_err(|e| format!("couldn't read stdin: {}", e))?;
+            input
+        },
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?,
+        None => Inputs::from_env(YEAR).load(args.day).map_err(|e| e.to_string())?
+    };
+
+    Ok(input.trim_end_matches('\n').to_string())
+}
+
+/// Solves every part of a day that can stream its input from PATH, or from
+/// stdin if PATH is -, without reading it all in first.
+fn stream_input(stream: StreamSolver, path: &str) -> Result<Vec<Run>, String> {
+    match path {
+        "-" => stream(&mut std::io::stdin().lock()),
+        path => {
+            let file = File::open(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+            stream(&mut BufReader::new(file))
+        }
+    }
+}
+
+fn print_report(args: &RunArgs, report: &Report) -> Result<(), String> {
+    if args.json {
+        println!("{}", serde_json::to_string(report).map_err(|e| format!("couldn't write JSON: {}", e))?);
+    } else {
+        let prefix = if args.part.is_none() { format!("part {}: ", report.part) } else { String::new() };
+        let status = report.status.as_ref().map_or(String::new(), |status| format!(" [{}]", status));
+
+        if args.time {
+            let (parse, solve) = (Duration::from_nanos(report.timing.parse_ns), Duration::from_nanos(report.timing.solve_ns));
+            println!("{}{}{} (parse {:?}, solve {:?})", prefix, report.answer, status, parse, solve);
+        } else {
+            println!("{}{}{}", prefix, report.answer, status);
+        }
+    }
+
+    Ok(())
+}
+
+fn run(args: &RunArgs) -> Result<(), String> {
+    let day = registry::find(args.day).ok_or(format!("day {} isn't solved", args.day))?;
+    let variant = args.variant.as_deref().unwrap_or(DEFAULT_VARIANT);
+
+    let solvers = match args.part {
+        Some(part) => {
+            day.part(part).ok_or(format!("day {} has no part {}", args.day, part))?;
+
+            let solver = day.variant(part, variant)
+                .ok_or(format!("day {} part {} has no variant {:?}", args.day, part, variant))?;
+
+            vec![(part, solver)]
+        },
+        None => (1..=2).filter_map(|part| day.part(part).map(|solver| (part, solver))).collect()
+    };
+
+    // answers to any other input can't be checked
+    let ledger = match args.input {
+        None => Some(Ledger::from_env(YEAR).map_err(|e| e.to_string())?),
+        Some(_) => None
+    };
+
+    let mut failed = vec![];
+
+    let mut report = |part: u32, run: Run| {
+        let mut report = Report::new(args.day, part, variant, run);
+        report.status = ledger.as_ref().map(|ledger| ledger.check(args.day, part, &report.answer));
+
+        if let Some(Status::Fail { .. }) = report.status {
+            failed.push(part);
+        }
+
+        print_report(args, &report)
+    };
+
+    match (day.stream, args.input.as_deref()) {
+        (Some(stream), Some(path)) if variant == DEFAULT_VARIANT => {
+            for (part, run) in (1..).zip(stream_input(stream, path)?) {
+                if solvers.iter().any(|&(p, _)| p == part) {
+                    report(part, run)?;
+                }
+            }
+        },
+        _ => {
+            let input = read_input(args)?;
+
+            for (part, solver) in solvers {
+                report(part, solver(&input)?)?;
+            }
+        }
+    }
+
+    match (failed.as_slice(), &ledger) {
+        ([], _) | (_, None) => Ok(()),
+        ([part], Some(ledger)) => Err(format!("day {} part {} doesn't match {}", args.day, part, ledger.path.display())),
+        (_, Some(ledger)) => Err(format!("day {} doesn't match {}", args.day, ledger.path.display()))
+    }
+}
+
+fn submit(args: &SubmitArgs) -> Result<(), String> {
+    let answer = match &args.answer {
+        Some(answer) => Answer::Text(answer.clone()),
+        None => {
+            let day = registry::find(args.day).ok_or(format!("day {} isn't solved", args.day))?;
+            let solver = day.part(args.part).ok_or(format!("day {} part {} isn't solved", args.day, args.part))?;
+            let input = Inputs::from_env(YEAR).load(args.day).map_err(|e| e.to_string())?;
+
+            solver(input.trim_end_matches('\n'))?.answer
+        }
+    };
+
+    let mut ledger = Ledger::from_env(YEAR).map_err(|e| e.to_string())?;
+    let verdict = ledger.submit(&Server::from_env(), args.day, args.part, &answer).map_err(|e| e.to_string())?;
+
+    println!("{}: {}", answer, verdict);
+    Ok(())
+}
+
+fn fetch(args: &FetchArgs) -> Result<(), String> {
+    let inputs = Inputs::from_env(YEAR);
+
+    let days = match args.day {
+        Some(day) => vec![day],
+        None => registry::DAYS.iter().map(|d| d.day).collect()
+    };
+
+    for day in days {
+        if args.force {
+            inputs.fetch(day)
+        } else {
+            inputs.load(day)
+        }.map_err(|e| e.to_string())?;
+
+        println!("{}", inputs.path(day).display());
+    }
+
+    Ok(())
+}
+
+fn generate(args: &GenerateArgs) -> Result<(), String> {
+    let input = generate::input(args.day, args.seed, args.scale)
+        .ok_or(format!("there's no generator for day {}", args.day))?;
+
+    // generated inputs are often piped into something like head, which may
+    // stop reading early
+    match writeln!(std::io::stdout(), "{}", input) {
+        Err(e) if e.kind() != ErrorKind::BrokenPipe => Err(format!("couldn't write input: {}", e)),
+        _ => Ok(())
+    }
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let command = match args.split_first() {
+        Some((cmd, rest)) if cmd == "run" => parse_run_args(rest).map(Command::Run),
+        Some((cmd, rest)) if cmd == "submit" => parse_submit_args(rest).map(Command::Submit),
+        Some((cmd, rest)) if cmd == "fetch" => parse_fetch_args(rest).map(Command::Fetch),
+        Some((cmd, rest)) if cmd == "generate" => parse_generate_args(rest).map(Command::Generate),
+        _ => Err("expected a command".to_string())
+    };
+
+    let command = command.unwrap_or_else(|e| {
+        eprintln!("error: {}\n\n{}", e, USAGE);
+        exit(2);
+    });
+
+    let result = match command {
+        Command::Run(args) => run(&args),
+        Command::Submit(args) => submit(&args),
+        Command::Fetch(args) => fetch(&args),
+        Command::Generate(args) => generate(&args)
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+}
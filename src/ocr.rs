@@ -0,0 +1,126 @@
+//! Reads the capital letters that Advent of Code draws out of dots.
+
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 6;
+const GLYPH_SPACING: usize = 1;
+
+/// Printed in place of a glyph that isn't in the font.
+pub const UNKNOWN: char = '?';
+
+const FONT: &[(char, [&str; GLYPH_HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+fn is_lit(grid: &[Vec<bool>], x: usize, y: usize) -> bool {
+    grid.get(y).and_then(|row| row.get(x)).cloned().unwrap_or(false)
+}
+
+fn read_glyph(grid: &[Vec<bool>], left: usize) -> char {
+    FONT.iter()
+        .find(|(_, rows)| {
+            rows.iter().enumerate().all(|(y, row)| {
+                row.chars().enumerate().all(|(x, c)| (c == '#') == is_lit(grid, left + x, y))
+            })
+        })
+        .map_or(UNKNOWN, |&(letter, _)| letter)
+}
+
+/// Splits `grid` (indexed `[y][x]`) into 4×6 glyphs one column apart and reads
+/// each one, substituting `UNKNOWN` for any it doesn't recognise. Columns past
+/// the last whole glyph are read as one more glyph, padded with dark cells, if
+/// any of them are lit.
+pub fn read_letters(grid: &[Vec<bool>]) -> String {
+    let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+    let stride = GLYPH_WIDTH + GLYPH_SPACING;
+    let count = (width + GLYPH_SPACING) / stride;
+
+    let mut letters = (0..count).map(|i| read_glyph(grid, i * stride)).collect::<String>();
+
+    let leftover_lit = (count * stride..width).any(|x| (0..grid.len()).any(|y| is_lit(grid, x, y)));
+    if leftover_lit {
+        letters.push(read_glyph(grid, count * stride));
+    }
+
+    letters
+}
+
+/// Draws `grid` with `#` for lit cells and `.` for dark ones, as the puzzle
+/// text does.
+pub fn render(grid: &[Vec<bool>]) -> Vec<String> {
+    grid.iter()
+        .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_grid<S: AsRef<str>>(rows: &[S]) -> Vec<Vec<bool>> {
+        rows.iter().map(|row| row.as_ref().chars().map(|c| c == '#').collect()).collect()
+    }
+
+    #[test]
+    fn reads_known_words() {
+        let grid = parse_grid(&[
+            "#..#..###",
+            "#..#...#.",
+            "####...#.",
+            "#..#...#.",
+            "#..#...#.",
+            "#..#..###"
+        ]);
+
+        assert_eq!(read_letters(&grid), "HI");
+        assert_eq!(render(&grid)[2], "####...#.");
+    }
+
+    #[test]
+    fn unknown_glyphs_are_marked() {
+        let grid = parse_grid(&[
+            "####.#...",
+            "#..#.#...",
+            "#..#.#...",
+            "#..#.#...",
+            "#..#.#...",
+            "####.####"
+        ]);
+
+        assert_eq!(read_letters(&grid), "?L");
+    }
+
+    #[test]
+    fn reads_grids_that_are_not_whole_glyphs_wide() {
+        let letter_l = ["#...", "#...", "#...", "#...", "#...", "####"];
+        let padded = |suffix: &str| letter_l.iter().map(|row| format!("{}{}", row, suffix)).collect::<Vec<_>>();
+
+        // trailing dark columns are just spacing
+        assert_eq!(read_letters(&parse_grid(&padded("."))), "L");
+        assert_eq!(read_letters(&parse_grid(&padded("..."))), "L");
+
+        // a stray lit column doesn't disappear
+        let mut stray = padded("..");
+        stray[0].push('#');
+        assert_eq!(read_letters(&parse_grid(&stray)), "L?");
+
+        // a glyph cut short is still read, as if the rest were dark
+        assert_eq!(read_letters(&parse_grid(&padded(".#"))), "L?");
+        assert_eq!(read_letters(&[]), "");
+    }
+}
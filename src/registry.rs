@@ -0,0 +1,242 @@
+//! Every day's solutions, so they can be looked up by day and part without
+//! going through cargo-aoc.
+
+use std::io::BufRead;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::ledger::Status;
+use crate::{Answer, Solution};
+
+/// Solves one part from the raw puzzle input, with trailing newlines removed.
+pub type Solver = fn(&str) -> Result<Run, String>;
+
+/// Solves every part in one pass over a reader, for days whose input can be too
+/// big to read into memory first. The runs come back in part order.
+pub type StreamSolver = fn(&mut dyn BufRead) -> Result<Vec<Run>, String>;
+
+/// A solved part, along with how long each phase took.
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub answer: Answer,
+    pub visual: Option<Vec<String>>,
+    pub parse_time: Duration,
+    pub solve_time: Duration
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+
+    (result, start.elapsed())
+}
+
+/// Parses `input` with `parse` and solves it with `solve`, timing each.
+pub fn solve_with<T, A: Into<Answer>>(
+    input: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+    solve: impl Fn(&T) -> Result<A, String>
+) -> Result<Run, String> {
+    let (parsed, parse_time) = timed(|| parse(input));
+    let parsed = parsed?;
+    let (answer, solve_time) = timed(|| solve(&parsed));
+
+    Ok(Run { answer: answer?.into(), visual: None, parse_time, solve_time })
+}
+
+/// Solves every part with `solve` as it reads them from `reader`. Reading,
+/// parsing and solving all happen together, so each run's solve time is the
+/// whole pass and its parse time is zero.
+pub fn stream_with<A: Into<Answer>>(
+    reader: &mut dyn BufRead,
+    solve: impl FnOnce(&mut dyn BufRead) -> Result<Vec<A>, String>
+) -> Result<Vec<Run>, String> {
+    let (answers, solve_time) = timed(|| solve(reader));
+
+    Ok(answers?.into_iter()
+        .map(|answer| Run { answer: answer.into(), visual: None, parse_time: Duration::ZERO, solve_time })
+        .collect())
+}
+
+fn solve_part<S: Solution, A: Into<Answer>>(
+    input: &str,
+    part: u32,
+    solve: fn(&S::Input) -> Result<A, String>
+) -> Result<Run, String> {
+    let (parsed, parse_time) = timed(|| S::parse(input));
+    let parsed = parsed?;
+    let (answer, solve_time) = timed(|| solve(&parsed));
+
+    Ok(Run { answer: answer?.into(), visual: S::visual(part, &parsed), parse_time, solve_time })
+}
+
+pub fn part1<S: Solution>(input: &str) -> Result<Run, String> {
+    solve_part::<S, _>(input, 1, S::part1)
+}
+
+pub fn part2<S: Solution>(input: &str) -> Result<Run, String> {
+    solve_part::<S, _>(input, 2, S::part2)
+}
+
+/// Another way of solving a part, besides the day's `Puzzle`.
+pub struct Variant {
+    pub part: u32,
+    pub name: &'static str,
+    pub solver: Solver
+}
+
+/// What the day's `Puzzle` is called when listed alongside its variants.
+pub const DEFAULT_VARIANT: &str = "default";
+
+pub struct Day {
+    pub day: u32,
+    pub part1: Solver,
+    pub part2: Option<Solver>,
+    pub variants: &'static [Variant],
+    /// Solves the default variant of every part straight from a reader.
+    pub stream: Option<StreamSolver>
+}
+
+impl Day {
+    pub fn part(&self, part: u32) -> Option<Solver> {
+        match part {
+            1 => Some(self.part1),
+            2 => self.part2,
+            _ => None
+        }
+    }
+
+    /// The solver for `part` called `name`, which is either one of the day's
+    /// variants or [`DEFAULT_VARIANT`].
+    pub fn variant(&self, part: u32, name: &str) -> Option<Solver> {
+        if name == DEFAULT_VARIANT {
+            return self.part(part);
+        }
+
+        self.variants.iter()
+            .find(|v| v.part == part && v.name == name)
+            .map(|v| v.solver)
+    }
+}
+
+/// Nanoseconds spent in each phase of a run.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Timing {
+    pub parse_ns: u64,
+    pub solve_ns: u64
+}
+
+/// One solved part, in the shape `advent-2021 run --json` prints it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub day: u32,
+    pub part: u32,
+    pub variant: String,
+    pub answer: Answer,
+    pub timing: Timing,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visual: Option<Vec<String>>,
+    /// How the answer compares with the ledger, for runs on the real input.
+    #[serde(flatten)]
+    pub status: Option<Status>
+}
+
+impl Report {
+    pub fn new(day: u32, part: u32, variant: &str, run: Run) -> Self {
+        Report {
+            day,
+            part,
+            variant: variant.to_string(),
+            answer: run.answer,
+            timing: Timing {
+                parse_ns: run.parse_time.as_nanos() as u64,
+                solve_ns: run.solve_time.as_nanos() as u64
+            },
+            visual: run.visual,
+            status: None
+        }
+    }
+}
+
+pub const DAYS: &[Day] = &[
+    crate::day1::DAY,
+    crate::day2::DAY,
+    crate::day3::DAY,
+    crate::day4::DAY,
+    crate::day5::DAY,
+    crate::day6::DAY,
+    crate::day7::DAY,
+    crate::day8::DAY,
+    crate::day9::DAY,
+    crate::day10::DAY,
+    crate::day11::DAY,
+    crate::day13::DAY,
+    crate::day14::DAY,
+];
+
+pub fn find(day: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_agree_with_default() {
+        for day in DAYS {
+            let input = std::fs::read_to_string(format!("input/2021/day{}.txt", day.day)).unwrap();
+            let input = input.trim_end_matches('\n');
+
+            for variant in day.variants {
+                let expected = day.part(variant.part).unwrap()(input).unwrap().answer;
+                let answer = (variant.solver)(input).unwrap().answer;
+
+                assert_eq!(answer, expected, "day {} part {} {}", day.day, variant.part, variant.name);
+            }
+        }
+    }
+
+    #[test]
+    fn streams_agree_with_default() {
+        for day in DAYS {
+            let Some(stream) = day.stream else { continue };
+            let input = std::fs::read_to_string(format!("input/2021/day{}.txt", day.day)).unwrap();
+            let runs = stream(&mut input.as_bytes()).unwrap();
+
+            for (part, run) in (1..).zip(runs) {
+                let expected = day.part(part).unwrap()(input.trim_end_matches('\n')).unwrap().answer;
+
+                assert_eq!(run.answer, expected, "day {} part {}", day.day, part);
+            }
+        }
+    }
+
+    #[test]
+    fn reports_serialize_typed_answers() {
+        let run = find(13).unwrap().part(2).unwrap()(puzzle_input!(13)).unwrap();
+        let json = serde_json::to_value(Report::new(13, 2, DEFAULT_VARIANT, run)).unwrap();
+
+        assert_eq!(json["answer"], "RCPLAKHL");
+        assert_eq!(json["variant"], "default");
+        assert_eq!(json["visual"].as_array().unwrap().len(), 6);
+        assert!(json["timing"]["solve_ns"].is_u64());
+
+        let run = find(6).unwrap().part(2).unwrap()(puzzle_input!(6)).unwrap();
+        let mut report = Report::new(6, 2, DEFAULT_VARIANT, run);
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["answer"], 1574445493136u64);
+        assert_eq!(serde_json::to_value(Answer::from(crate::MAX_JSON_INTEGER)).unwrap(), crate::MAX_JSON_INTEGER);
+        assert_eq!(serde_json::to_value(Answer::from(crate::MAX_JSON_INTEGER + 1)).unwrap(), "9007199254740992");
+        assert_eq!(serde_json::to_value(Answer::from(-(1i64 << 53))).unwrap(), "-9007199254740992");
+        assert_eq!(serde_json::to_value(Answer::from(u128::MAX)).unwrap(), u128::MAX.to_string());
+        assert!(json.get("visual").is_none());
+        assert!(json.get("status").is_none());
+
+        report.status = Some(Status::Fail { expected: Some("1574445493137".to_string()) });
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["status"], "fail");
+        assert_eq!(json["expected"], "1574445493137");
+    }
+}
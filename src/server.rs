@@ -0,0 +1,71 @@
+//! The Advent of Code server, which hands out inputs and takes answers for
+//! whoever's session cookie comes with the request.
+
+use std::time::Duration;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+#[derive(Debug, Clone)]
+pub struct Server {
+    pub base_url: String,
+    pub session: Option<String>
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            session: None
+        }
+    }
+}
+
+impl Server {
+    /// Settings from the environment: `AOC_BASE_URL` and `AOC_SESSION`, with
+    /// the defaults for any that aren't set.
+    pub fn from_env() -> Self {
+        Server {
+            base_url: env_var("AOC_BASE_URL").unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            session: env_var("AOC_SESSION").map(|s| s.trim().to_string())
+        }
+    }
+
+    fn request(&self, method: &str, path: &str, session: &str) -> ureq::Request {
+        ureq::request(method, &format!("{}{}", self.base_url.trim_end_matches('/'), path))
+            .timeout(Duration::from_secs(30))
+            .set("Cookie", &format!("session={}", session))
+            .set("User-Agent", concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+    }
+
+    /// The body of the page at `path`. Errors are the status and first line of
+    /// the server's response, or why there wasn't one.
+    pub fn get(&self, path: &str, session: &str) -> Result<String, String> {
+        read_response(self.request("GET", path, session).call())
+    }
+
+    /// Posts `form` to `path`, with errors as for [`Server::get`].
+    pub fn post(&self, path: &str, session: &str, form: &[(&str, &str)]) -> Result<String, String> {
+        read_response(self.request("POST", path, session).send_form(form))
+    }
+}
+
+/// A setting from the environment, treating a blank one as unset.
+pub(crate) fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn read_response(response: Result<ureq::Response, ureq::Error>) -> Result<String, String> {
+    let response = response.map_err(|e| {
+        let message = match e {
+            ureq::Error::Status(status, response) => {
+                let body = response.into_string().unwrap_or_default();
+                format!("{} {}", status, body.lines().next().unwrap_or("").trim())
+            },
+            e => e.to_string()
+        };
+
+        message.trim().to_string()
+    })?;
+
+    response.into_string().map_err(|e| e.to_string())
+}
@@ -0,0 +1,99 @@
+//! The common shape of every day's puzzle.
+
+use std::fmt;
+use serde::{Serialize, Serializer};
+
+/// The largest integer that every JSON reader holds exactly. JavaScript and
+/// many others read JSON numbers as doubles, which go no further.
+pub const MAX_JSON_INTEGER: u64 = (1 << 53) - 1;
+
+/// A part's answer: a number, some text, or a picture given as rows of text.
+/// Serializes as a plain JSON number, string, or array of strings. A number
+/// beyond ±[`MAX_JSON_INTEGER`] is a string of its digits instead, so it reads
+/// back the same anywhere.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Number(#[serde(serialize_with = "serialize_number")] i64),
+    Text(String),
+    Grid(Vec<String>)
+}
+
+fn serialize_number<S: Serializer>(n: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    if n.unsigned_abs() <= MAX_JSON_INTEGER {
+        serializer.serialize_i64(*n)
+    } else {
+        serializer.collect_str(n)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n"))
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Self {
+        Answer::Number(n)
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(n: u32) -> Self {
+        Answer::Number(n.into())
+    }
+}
+
+/// Numbers too big for an `i64` are given as text, which serializes the same
+/// way as any other number beyond [`MAX_JSON_INTEGER`].
+macro_rules! wide_number_answer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    i64::try_from(n).map(Answer::Number).unwrap_or_else(|_| Answer::Text(n.to_string()))
+                }
+            }
+        )*
+    };
+}
+
+wide_number_answer!(u64, usize, u128);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+/// One day's puzzle: how to parse its input, and how to solve each part from
+/// the parsed input. Each `dayN` module implements this on its `Puzzle` type.
+///
+/// ```
+/// use advent_2021::Solution;
+/// use advent_2021::day1::Puzzle;
+///
+/// let depths = Puzzle::parse("199\n200\n208\n210\n200\n207\n240\n269\n260\n263").unwrap();
+/// assert_eq!(Puzzle::part1(&depths).unwrap(), 7);
+/// assert_eq!(Puzzle::part2(&depths).unwrap(), 5);
+/// ```
+pub trait Solution {
+    type Input;
+    type Part1: Into<Answer>;
+    type Part2: Into<Answer>;
+
+    fn parse(input: &str) -> Result<Self::Input, String>;
+    fn part1(input: &Self::Input) -> Result<Self::Part1, String>;
+    fn part2(input: &Self::Input) -> Result<Self::Part2, String>;
+
+    /// A picture to go with a part's answer, for answers that are read off a
+    /// drawing. Most parts don't have one.
+    fn visual(_part: u32, _input: &Self::Input) -> Option<Vec<String>> {
+        None
+    }
+}